@@ -0,0 +1,106 @@
+use anyhow::{bail, Result};
+use std::io::Write;
+use tracing::info;
+
+/// How the map is presented.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum OutputFormat {
+    /// The indented tree and summaries on stdout
+    #[default]
+    Text,
+    /// The versioned JSON document (see `export::json`)
+    Json,
+    /// A GraphViz digraph
+    Dot,
+    /// A Mermaid flowchart
+    Mermaid,
+    /// A self-contained interactive page
+    Html,
+}
+
+impl OutputFormat {
+    fn parse(name: &str) -> Result<Self> {
+        match name {
+            "text" => Ok(Self::Text),
+            "json" => Ok(Self::Json),
+            "dot" => Ok(Self::Dot),
+            "mermaid" => Ok(Self::Mermaid),
+            "html" => Ok(Self::Html),
+            _ => bail!("Unknown output format {name} (expected text, json, dot, mermaid or html)"),
+        }
+    }
+}
+
+/// Command-line arguments.
+#[derive(Debug, Default)]
+pub struct Args {
+    pub output: OutputFormat,
+    /// Write the output here instead of stdout
+    pub output_file: Option<String>,
+}
+
+impl Args {
+    pub fn from_env() -> Result<Self> {
+        Self::parse(std::env::args().skip(1))
+    }
+
+    fn parse(mut args: impl Iterator<Item = String>) -> Result<Self> {
+        let mut result = Args::default();
+        while let Some(arg) = args.next() {
+            let (flag, inline) = match arg.split_once('=') {
+                Some((flag, value)) => (flag.to_string(), Some(value.to_string())),
+                None => (arg, None),
+            };
+            let mut value = || -> Result<String> {
+                match inline.clone().or_else(|| args.next()) {
+                    Some(value) => Ok(value),
+                    None => bail!("{flag} needs a value"),
+                }
+            };
+            match flag.as_str() {
+                "-o" | "--output" => result.output = OutputFormat::parse(&value()?)?,
+                "--output-file" => result.output_file = Some(value()?),
+                _ => bail!("Unknown argument {flag}. Usage: router_mapper [--output text|json|dot|mermaid|html] [--output-file FILE]"),
+            }
+        }
+        Ok(result)
+    }
+
+    /// Where to write the map: `--output-file` if given, otherwise stdout.
+    pub fn output_writer(&self) -> Result<Box<dyn Write>> {
+        match &self.output_file {
+            Some(filename) => {
+                info!("Writing {:?} map to {filename}", self.output);
+                Ok(Box::new(std::fs::File::create(filename)?))
+            }
+            None => Ok(Box::new(std::io::stdout().lock())),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn parse(args: &[&str]) -> Result<Args> {
+        Args::parse(args.iter().map(|a| a.to_string()))
+    }
+
+    #[test]
+    fn test_parse_args() {
+        assert_eq!(parse(&[]).unwrap().output, OutputFormat::Text);
+        assert_eq!(
+            parse(&["--output", "json"]).unwrap().output,
+            OutputFormat::Json
+        );
+        let args = parse(&["--output=json", "--output-file", "map.json"]).unwrap();
+        assert_eq!(args.output, OutputFormat::Json);
+        assert_eq!(args.output_file.as_deref(), Some("map.json"));
+        assert_eq!(
+            parse(&["-o", "mermaid"]).unwrap().output,
+            OutputFormat::Mermaid
+        );
+        assert!(parse(&["--output", "yaml"]).is_err());
+        assert!(parse(&["--output"]).is_err());
+    }
+}
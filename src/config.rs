@@ -1,19 +1,58 @@
+use crate::{
+    export::LibreQosConfig,
+    router::{ProfileConfig, WirelessTable},
+};
+use ipnetwork::IpNetwork;
 use once_cell::sync::Lazy;
 use serde::Deserialize;
-use std::path::Path;
+use std::{net::IpAddr, path::Path};
 
 /// Global configuration
-pub static CONFIG: Lazy<Config> = Lazy::new(|| Config::load());
+pub static CONFIG: Lazy<Config> = Lazy::new(Config::load);
 
 #[derive(Debug, Deserialize)]
+#[serde(default)]
 pub struct Config {
     pub enable_next_hop_lookup: bool,
+    pub max_next_hop_depth: usize,
+    /// Prefixes whose route is treated as "upstream" on routers that
+    /// have no default route.
+    pub upstream_prefixes: Vec<IpNetwork>,
+    /// Address of the management network; the route towards it is used as
+    /// a last resort when looking for a router's parent.
+    pub management_seed: Option<IpAddr>,
+    /// Subnets (larger than a point-to-point) shared by more routers than
+    /// this are flagged as suspicious.
+    pub max_routers_per_lan: usize,
+    /// If set, write a per-site hardware inventory CSV to this file.
+    pub inventory_csv: Option<String>,
+    /// If set, write each router's CPU/memory/temperature readings to this file.
+    pub health_csv: Option<String>,
+    /// If set, sample interface counters twice this many seconds apart and
+    /// attach the resulting utilisation to the topology's links.
+    pub traffic_sample_seconds: Option<u64>,
+    /// Extra vendor tables to read radio link statistics from
+    pub wireless_tables: Vec<WirelessTable>,
+    /// User-defined vendor profiles, tried before the built-in ones
+    pub profiles: Vec<ProfileConfig>,
+    /// Exports for LibreQoS
+    pub libreqos: LibreQosConfig,
 }
 
 impl Default for Config {
     fn default() -> Self {
         Self {
             enable_next_hop_lookup: false,
+            max_next_hop_depth: 8,
+            upstream_prefixes: Vec::new(),
+            management_seed: None,
+            max_routers_per_lan: 8,
+            inventory_csv: None,
+            health_csv: None,
+            traffic_sample_seconds: None,
+            wireless_tables: Vec::new(),
+            profiles: Vec::new(),
+            libreqos: LibreQosConfig::default(),
         }
     }
 }
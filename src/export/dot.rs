@@ -0,0 +1,85 @@
+use super::graph::{EdgeStyle, Graph, GraphNode};
+use crate::topology::Topology;
+use anyhow::Result;
+use std::io::Write;
+
+fn quote(text: &str) -> String {
+    let escaped = text
+        .replace('\\', "\\\\")
+        .replace('"', "\\\"")
+        .replace('\n', "\\n");
+    format!("\"{escaped}\"")
+}
+
+/// A multi-line label, one line per entry.
+fn label(lines: &[String]) -> String {
+    quote(&lines.join("\n"))
+}
+
+fn node(node: &GraphNode) -> String {
+    format!("n{} [label={}];", node.id, label(&node.lines))
+}
+
+/// Write the topology as a GraphViz digraph, with parents above their
+/// children and each site drawn as a cluster. Parents confirmed by a
+/// neighbor protocol or OSPF are bold, those inferred from routes alone are
+/// dashed, and other neighbor/OSPF links are grey dotted lines.
+pub fn write_dot(topology: &Topology, mut writer: impl Write) -> Result<()> {
+    let graph = Graph::new(topology);
+    writeln!(writer, "digraph topology {{")?;
+    writeln!(writer, "    node [shape=box, fontname=\"Helvetica\"];")?;
+    writeln!(writer, "    edge [fontname=\"Helvetica\", fontsize=10];")?;
+    for (index, (site, nodes)) in graph.sites.iter().enumerate() {
+        writeln!(writer, "    subgraph cluster_{index} {{")?;
+        writeln!(writer, "        label={};", quote(site))?;
+        for id in nodes {
+            writeln!(writer, "        {}", node(&graph.nodes[*id]))?;
+        }
+        writeln!(writer, "    }}")?;
+    }
+    for unsited in graph.unsited() {
+        writeln!(writer, "    {}", node(unsited))?;
+    }
+    for edge in graph.edges.iter() {
+        let style = match edge.style {
+            EdgeStyle::Confirmed => "style=bold",
+            EdgeStyle::Inferred => "style=dashed",
+            EdgeStyle::Peer => "style=dotted, color=gray, dir=none, constraint=false",
+        };
+        writeln!(
+            writer,
+            "    n{} -> n{} [label={}, {style}];",
+            edge.from,
+            edge.to,
+            label(&edge.lines)
+        )?;
+    }
+    writeln!(writer, "}}")?;
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::router::fixtures::{route, router};
+
+    #[test]
+    fn test_dot() {
+        let mut core = router("core", &["10.0.0.1/30"], vec![]);
+        core.system_info.location = "Main \"POP\"".to_string();
+        let topology = Topology::build(vec![
+            core,
+            router(
+                "agg",
+                &["10.0.0.2/30"],
+                vec![route("0.0.0.0", 0, "10.0.0.1")],
+            ),
+        ]);
+        let mut buffer = Vec::new();
+        write_dot(&topology, &mut buffer).unwrap();
+        let dot = String::from_utf8(buffer).unwrap();
+        assert!(dot.contains("    subgraph cluster_0 {\n        label=\"Main \\\"POP\\\"\";\n        n0 [label=\"core\\n10.0.0.1\"];\n    }\n"));
+        assert!(dot.contains("    n1 [label=\"agg\\n10.0.0.2\"];\n"));
+        assert!(dot.contains("    n0 -> n1 [label=\"10.0.0.1\", style=dashed];\n"));
+    }
+}
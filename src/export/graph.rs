@@ -0,0 +1,232 @@
+//! The picture-friendly view of a topology shared by the DOT, Mermaid and
+//! HTML renderers: labelled routers grouped by site, and the edges worth
+//! drawing between them.
+use crate::topology::{Edge, EdgeKind, Evidence, NodeId, Topology};
+use std::collections::BTreeMap;
+
+/// How much we trust an edge, which decides how it is drawn.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(super) enum EdgeStyle {
+    /// A parent inferred from the routing table alone
+    Inferred,
+    /// A parent also seen as an LLDP/CDP/MNDP neighbor or OSPF adjacency
+    Confirmed,
+    /// Neighbors or OSPF adjacencies that aren't a parent relationship
+    Peer,
+}
+
+pub(super) struct GraphNode {
+    pub id: NodeId,
+    /// Hostname, platform, management address
+    pub lines: Vec<String>,
+}
+
+pub(super) struct GraphEdge {
+    /// The parent, for parent edges
+    pub from: NodeId,
+    pub to: NodeId,
+    /// Next hop and interface for parent edges, protocols for peers
+    pub lines: Vec<String>,
+    pub style: EdgeStyle,
+}
+
+pub(super) struct Graph {
+    pub nodes: Vec<GraphNode>,
+    /// Site -> nodes; nodes without a site aren't listed
+    pub sites: BTreeMap<String, Vec<NodeId>>,
+    pub edges: Vec<GraphEdge>,
+}
+
+/// Is there an LLDP/CDP/MNDP or OSPF edge between `a` and `b`?
+fn confirmed(topology: &Topology, a: NodeId, b: NodeId) -> bool {
+    topology
+        .edges_of(a)
+        .any(|e| matches!(e.kind, EdgeKind::Neighbor | EdgeKind::OspfAdjacency) && e.connects(a, b))
+}
+
+/// Label a parent edge with the next hop the child uses and the
+/// interface it leaves by.
+fn parent_lines(topology: &Topology, edge: &Edge) -> Vec<String> {
+    let Some(next_hop) = edge.evidence.iter().find_map(|e| match e {
+        Evidence::UpstreamRoute { route, resolution } => Some(
+            resolution
+                .as_ref()
+                .and_then(|r| r.connected_next_hop())
+                .unwrap_or(route.next_hop),
+        ),
+        _ => None,
+    }) else {
+        return Vec::new();
+    };
+    let mut lines = vec![next_hop.to_string()];
+    if let Some(interface) = topology.nodes[edge.from].router.interface_towards(next_hop) {
+        lines.push(interface.short_name().to_string());
+    }
+    lines
+}
+
+fn protocol_name(evidence: &Evidence) -> Option<&'static str> {
+    match evidence {
+        Evidence::Neighbor { protocol, .. } => Some(protocol.name()),
+        Evidence::OspfAdjacency { .. } => Some("OSPF"),
+        _ => None,
+    }
+}
+
+impl Graph {
+    pub fn new(topology: &Topology) -> Self {
+        let mut sites: BTreeMap<String, Vec<NodeId>> = BTreeMap::new();
+        let nodes = topology
+            .nodes
+            .iter()
+            .map(|node| {
+                let router = &node.router;
+                if !router.site().is_empty() {
+                    sites
+                        .entry(router.site().to_string())
+                        .or_default()
+                        .push(node.id);
+                }
+                let lines = [
+                    node.name.clone(),
+                    router.fingerprint.platform(),
+                    router.connection.snmp_address.clone(),
+                ]
+                .into_iter()
+                .filter(|line| !line.is_empty())
+                .collect();
+                GraphNode { id: node.id, lines }
+            })
+            .collect();
+
+        let mut edges: Vec<GraphEdge> = topology
+            .edges
+            .iter()
+            .filter(|e| e.kind == EdgeKind::Parent)
+            .map(|e| GraphEdge {
+                from: e.to,
+                to: e.from,
+                lines: parent_lines(topology, e),
+                style: if confirmed(topology, e.from, e.to) {
+                    EdgeStyle::Confirmed
+                } else {
+                    EdgeStyle::Inferred
+                },
+            })
+            .collect();
+
+        // One line per pair of peers, naming every protocol that saw it
+        let mut peers: BTreeMap<(NodeId, NodeId), Vec<&str>> = BTreeMap::new();
+        for edge in topology.edges.iter() {
+            let pair = (edge.from.min(edge.to), edge.from.max(edge.to));
+            if edges
+                .iter()
+                .any(|e| (e.from.min(e.to), e.from.max(e.to)) == pair)
+            {
+                continue;
+            }
+            for name in edge.evidence.iter().filter_map(protocol_name) {
+                let names = peers.entry(pair).or_default();
+                if !names.contains(&name) {
+                    names.push(name);
+                }
+            }
+        }
+        edges.extend(peers.into_iter().map(|((from, to), names)| GraphEdge {
+            from,
+            to,
+            lines: vec![names.join(", ")],
+            style: EdgeStyle::Peer,
+        }));
+
+        Self {
+            nodes,
+            sites,
+            edges,
+        }
+    }
+
+    /// Nodes that aren't in any site.
+    pub fn unsited(&self) -> impl Iterator<Item = &GraphNode> {
+        self.nodes
+            .iter()
+            .filter(|n| !self.sites.values().any(|site| site.contains(&n.id)))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::router::{
+        fixtures::{route, router},
+        Interface, NeighborProtocol,
+    };
+
+    // core <- agg <- edge, with agg also an LLDP neighbor of core, and
+    // edge an OSPF neighbor of core
+    fn test_topology() -> Topology {
+        let mut core = router("core", &["10.0.0.1/30", "10.0.2.1/30"], vec![]);
+        core.system_info.location = "Datacenter".to_string();
+        let mut agg = router(
+            "agg",
+            &["10.0.0.2/30", "10.0.1.1/29"],
+            vec![route("0.0.0.0", 0, "10.0.0.1")],
+        );
+        agg.system_info.location = "Datacenter".to_string();
+        agg.interfaces.interfaces.push(Interface {
+            index: 1,
+            name: "ether1".to_string(),
+            ..Default::default()
+        });
+        let edge = router(
+            "edge",
+            &["10.0.1.2/29", "10.0.2.2/30"],
+            vec![route("0.0.0.0", 0, "10.0.1.1")],
+        );
+        let mut topology = Topology::build(vec![core, agg, edge]);
+        topology.add_edge(
+            1,
+            0,
+            EdgeKind::Neighbor,
+            0.9,
+            Evidence::Neighbor {
+                protocol: NeighborProtocol::Lldp,
+                local_port: "ether1".to_string(),
+                remote_port: "ether5".to_string(),
+            },
+        );
+        topology.add_edge(
+            2,
+            0,
+            EdgeKind::OspfAdjacency,
+            0.9,
+            Evidence::OspfAdjacency {
+                area: None,
+                state: Default::default(),
+            },
+        );
+        topology
+    }
+
+    #[test]
+    fn test_graph() {
+        let graph = Graph::new(&test_topology());
+        assert_eq!(graph.nodes[1].lines, vec!["agg", "10.0.0.2"]);
+        assert_eq!(graph.sites["Datacenter"], vec![0, 1]);
+        assert_eq!(graph.unsited().map(|n| n.id).collect::<Vec<_>>(), vec![2]);
+
+        let agg = graph.edges.iter().find(|e| e.to == 1).unwrap();
+        assert_eq!(agg.from, 0);
+        assert_eq!(agg.lines, vec!["10.0.0.1", "ether1"]);
+        assert_eq!(agg.style, EdgeStyle::Confirmed);
+        let edge = graph.edges.iter().find(|e| e.to == 2).unwrap();
+        assert_eq!(edge.style, EdgeStyle::Inferred);
+        let peer = graph
+            .edges
+            .iter()
+            .find(|e| e.style == EdgeStyle::Peer)
+            .unwrap();
+        assert_eq!((peer.from, peer.to), (0, 2));
+        assert_eq!(peer.lines, vec!["OSPF"]);
+    }
+}
@@ -0,0 +1,46 @@
+use crate::topology::Topology;
+use anyhow::Result;
+use serde::Serialize;
+
+/// One router's health readings.
+#[derive(Debug, Serialize)]
+struct HealthRow<'a> {
+    site: &'a str,
+    router: &'a str,
+    management_address: &'a str,
+    uptime_seconds: u64,
+    cpu_percent: Option<f64>,
+    memory_percent: Option<f64>,
+    memory_total_bytes: Option<u64>,
+    /// The fullest disk
+    disk_percent: Option<f64>,
+    max_temperature_celsius: Option<f64>,
+    problems: String,
+}
+
+/// Write each router's CPU, memory, disk and temperature readings to a CSV.
+pub fn write_health_csv(topology: &Topology, filename: &str) -> Result<()> {
+    let mut writer = csv::Writer::from_path(filename)?;
+    for node in topology.nodes.iter() {
+        let router = &node.router;
+        let health = &router.health;
+        writer.serialize(HealthRow {
+            site: router.site(),
+            router: &node.name,
+            management_address: &router.connection.snmp_address,
+            uptime_seconds: router.system_info.uptime_seconds(),
+            cpu_percent: health.cpu_percent(),
+            memory_percent: health.memory_percent(),
+            memory_total_bytes: health.memory.as_ref().map(|m| m.total_bytes),
+            disk_percent: health
+                .disks
+                .iter()
+                .filter_map(|d| d.used_percent())
+                .max_by(|a, b| a.total_cmp(b)),
+            max_temperature_celsius: health.max_temperature(),
+            problems: health.problems().join("; "),
+        })?;
+    }
+    writer.flush()?;
+    Ok(())
+}
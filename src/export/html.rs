@@ -0,0 +1,226 @@
+use super::graph::{EdgeStyle, Graph};
+use crate::router::Router;
+use crate::topology::{NodeId, Severity, Topology};
+use anyhow::Result;
+use serde::Serialize;
+use std::io::Write;
+
+/// The page, with its styles and script inline so it works offline.
+const TEMPLATE: &str = include_str!("report.html");
+/// Replaced with the report's data
+const DATA_MARKER: &str = "/*REPORT_DATA*/";
+
+/// Everything the page's script draws from.
+#[derive(Debug, Serialize)]
+struct Report<'a> {
+    generator: String,
+    routers: Vec<RouterEntry<'a>>,
+    edges: Vec<EdgeEntry>,
+}
+
+#[derive(Debug, Serialize)]
+struct RouterEntry<'a> {
+    id: NodeId,
+    name: &'a str,
+    site: &'a str,
+    platform: String,
+    address: &'a str,
+    parent: Option<NodeId>,
+    /// This router, then each preferred parent up to a root
+    path_to_root: Vec<NodeId>,
+    addresses: Vec<AddressEntry>,
+    routes: Vec<RouteEntry>,
+    neighbors: Vec<NeighborEntry<'a>>,
+    findings: Vec<FindingEntry<'a>>,
+}
+
+#[derive(Debug, Serialize)]
+struct AddressEntry {
+    address: String,
+    interface: String,
+}
+
+#[derive(Debug, Serialize)]
+struct RouteEntry {
+    destination: String,
+    next_hop: String,
+    interface: String,
+}
+
+#[derive(Debug, Serialize)]
+struct NeighborEntry<'a> {
+    protocol: &'static str,
+    local_port: String,
+    name: &'a str,
+    remote_port: &'a str,
+    addresses: String,
+}
+
+#[derive(Debug, Serialize)]
+struct FindingEntry<'a> {
+    severity: Severity,
+    message: &'a str,
+}
+
+#[derive(Debug, Serialize)]
+struct EdgeEntry {
+    from: NodeId,
+    to: NodeId,
+    /// "confirmed", "inferred" or "peer"
+    style: &'static str,
+    label: String,
+}
+
+fn interface_name(router: &Router, index: i32) -> String {
+    router
+        .interfaces
+        .get(index)
+        .map(|i| i.short_name().to_string())
+        .unwrap_or_default()
+}
+
+impl<'a> Report<'a> {
+    fn new(topology: &'a Topology) -> Self {
+        let graph = Graph::new(topology);
+        let routers = topology
+            .nodes
+            .iter()
+            .map(|node| {
+                let router = &node.router;
+                RouterEntry {
+                    id: node.id,
+                    name: &node.name,
+                    site: router.site(),
+                    platform: router.fingerprint.platform(),
+                    address: &router.connection.snmp_address,
+                    parent: topology.preferred_parent(node.id),
+                    path_to_root: topology.path_to_root(node.id),
+                    addresses: router
+                        .ip_table
+                        .ips
+                        .iter()
+                        .map(|ip| AddressEntry {
+                            address: ip
+                                .ip_network()
+                                .map_or_else(|| ip.address.to_string(), |net| net.to_string()),
+                            interface: interface_name(router, ip.interface_index),
+                        })
+                        .collect(),
+                    routes: router
+                        .ip_routes
+                        .routes
+                        .iter()
+                        .map(|route| RouteEntry {
+                            destination: format!("{}/{}", route.destination, route.netmask),
+                            next_hop: route.next_hop.to_string(),
+                            interface: interface_name(router, route.if_index),
+                        })
+                        .collect(),
+                    neighbors: router
+                        .neighbors
+                        .neighbors
+                        .iter()
+                        .map(|neighbor| NeighborEntry {
+                            protocol: neighbor.protocol.name(),
+                            local_port: router
+                                .neighbor_interface(neighbor)
+                                .map(|i| i.short_name().to_string())
+                                .unwrap_or_else(|| neighbor.local_port_name.clone()),
+                            name: &neighbor.system_name,
+                            remote_port: if neighbor.port_description.is_empty() {
+                                &neighbor.port_id
+                            } else {
+                                &neighbor.port_description
+                            },
+                            addresses: neighbor
+                                .management_addresses
+                                .iter()
+                                .map(|a| a.to_string())
+                                .collect::<Vec<_>>()
+                                .join(", "),
+                        })
+                        .collect(),
+                    findings: topology
+                        .findings
+                        .iter()
+                        .filter(|f| f.nodes.contains(&node.id))
+                        .map(|f| FindingEntry {
+                            severity: f.severity,
+                            message: &f.message,
+                        })
+                        .collect(),
+                }
+            })
+            .collect();
+        let edges = graph
+            .edges
+            .iter()
+            .map(|edge| EdgeEntry {
+                from: edge.from,
+                to: edge.to,
+                style: match edge.style {
+                    EdgeStyle::Confirmed => "confirmed",
+                    EdgeStyle::Inferred => "inferred",
+                    EdgeStyle::Peer => "peer",
+                },
+                label: edge.lines.join(" "),
+            })
+            .collect();
+        Self {
+            generator: format!("{} {}", env!("CARGO_PKG_NAME"), env!("CARGO_PKG_VERSION")),
+            routers,
+            edges,
+        }
+    }
+}
+
+/// Write a single self-contained HTML page: a zoomable force-directed map,
+/// a searchable router table, and a detail pane for the selected router
+/// with its path to the root highlighted on the map.
+pub fn write_html(topology: &Topology, mut writer: impl Write) -> Result<()> {
+    // The data sits inside a <script> element, so it mustn't contain "</"
+    let data = serde_json::to_string(&Report::new(topology))?.replace('<', "\\u003c");
+    writer.write_all(TEMPLATE.replace(DATA_MARKER, &data).as_bytes())?;
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::router::fixtures::{route, router};
+
+    #[test]
+    fn test_html_report() {
+        let topology = Topology::build(vec![
+            router("core", &["10.0.0.1/30"], vec![]),
+            router(
+                "agg",
+                &["10.0.0.2/30", "10.0.1.1/29"],
+                vec![route("0.0.0.0", 0, "10.0.0.1")],
+            ),
+            router(
+                "</script><b>edge",
+                &["10.0.1.2/29"],
+                vec![route("0.0.0.0", 0, "10.0.1.1")],
+            ),
+        ]);
+        let mut buffer = Vec::new();
+        write_html(&topology, &mut buffer).unwrap();
+        let html = String::from_utf8(buffer).unwrap();
+        assert!(html.starts_with("<!DOCTYPE html>"));
+        assert!(!html.contains(DATA_MARKER));
+        assert!(!html.contains("</script><b>"));
+
+        let marker = "id=\"report-data\">";
+        let start = html.find(marker).unwrap() + marker.len();
+        let end = start + html[start..].find("</script>").unwrap();
+        let data: serde_json::Value = serde_json::from_str(&html[start..end]).unwrap();
+        assert_eq!(data["routers"][2]["name"], "</script><b>edge");
+        assert_eq!(
+            data["routers"][2]["path_to_root"],
+            serde_json::json!([2, 1, 0])
+        );
+        assert_eq!(data["routers"][1]["routes"][0]["next_hop"], "10.0.0.1");
+        assert_eq!(data["edges"][0]["style"], "inferred");
+    }
+}
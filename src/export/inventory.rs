@@ -0,0 +1,81 @@
+use crate::{router::Vendor, topology::Topology};
+use anyhow::Result;
+use serde::Serialize;
+
+/// One line of the hardware inventory.
+#[derive(Debug, Serialize)]
+struct InventoryRow<'a> {
+    site: &'a str,
+    router: &'a str,
+    management_address: &'a str,
+    depth: usize,
+    class: String,
+    name: &'a str,
+    description: &'a str,
+    manufacturer: &'a str,
+    model: &'a str,
+    serial_number: &'a str,
+    hardware_revision: &'a str,
+    firmware_revision: &'a str,
+    software_revision: &'a str,
+}
+
+/// Write every router's ENTITY-MIB assets to a CSV, grouped by site (site
+/// tag or sysLocation). Routers that don't implement ENTITY-MIB get one row
+/// describing the platform from its fingerprint, so they still appear in
+/// the list.
+pub fn write_inventory_csv(topology: &Topology, filename: &str) -> Result<()> {
+    let mut nodes: Vec<_> = topology.nodes.iter().collect();
+    nodes.sort_by(|a, b| (a.router.site(), &a.name).cmp(&(b.router.site(), &b.name)));
+
+    let mut writer = csv::Writer::from_path(filename)?;
+    for node in nodes {
+        let router = &node.router;
+        let site = router.site();
+        let mut wrote_any = false;
+        for (depth, entity) in router.inventory.tree() {
+            if !entity.is_asset() {
+                continue;
+            }
+            writer.serialize(InventoryRow {
+                site,
+                router: &node.name,
+                management_address: &router.connection.snmp_address,
+                depth,
+                class: format!("{:?}", entity.class),
+                name: &entity.name,
+                description: &entity.description,
+                manufacturer: &entity.manufacturer,
+                model: &entity.model_name,
+                serial_number: &entity.serial_number,
+                hardware_revision: &entity.hardware_revision,
+                firmware_revision: &entity.firmware_revision,
+                software_revision: &entity.software_revision,
+            })?;
+            wrote_any = true;
+        }
+        if !wrote_any {
+            let fingerprint = &router.fingerprint;
+            writer.serialize(InventoryRow {
+                site,
+                router: &node.name,
+                management_address: &router.connection.snmp_address,
+                depth: 0,
+                class: "Chassis".to_string(),
+                name: "",
+                description: &router.system_info.platform,
+                manufacturer: match fingerprint.vendor {
+                    Vendor::Unknown => "",
+                    vendor => vendor.name(),
+                },
+                model: &fingerprint.model,
+                serial_number: "",
+                hardware_revision: "",
+                firmware_revision: "",
+                software_revision: &fingerprint.os_version,
+            })?;
+        }
+    }
+    writer.flush()?;
+    Ok(())
+}
@@ -0,0 +1,170 @@
+use crate::router::{
+    ArpEntry, Bgp, Bridge, CidrEntry, Fingerprint, Health, Interface, InterfaceRates, IpAddress,
+    Neighbor, NextHopResolution, Ospf, PhysicalEntity, SystemInfo, UpstreamRoute, Vrf,
+    WirelessLink,
+};
+use crate::topology::{Edge, EdgeKind, Evidence, Finding, Link, NodeId, Topology, UnlistedDevice};
+use anyhow::Result;
+use serde::Serialize;
+use std::{collections::BTreeMap, io::Write};
+
+/// Bumped whenever a field is renamed, removed or changes meaning. Adding
+/// fields does not change the version, so consumers should ignore fields
+/// they don't know.
+pub const SCHEMA_VERSION: u32 = 1;
+
+/// The whole map, as written by `--output json`.
+#[derive(Debug, Serialize)]
+struct Document<'a> {
+    schema_version: u32,
+    generator: String,
+    routers: Vec<RouterDoc<'a>>,
+    /// Child -> parent relationships, with the evidence for each
+    parents: Vec<ParentDoc<'a>>,
+    /// Every other edge (neighbors, BGP, OSPF, wireless, shared subnets)
+    edges: Vec<&'a Edge>,
+    links: &'a [Link],
+    findings: &'a [Finding],
+    unlisted_devices: &'a [UnlistedDevice],
+}
+
+/// One polled router. `id` is what `parents`, `edges` and `links` refer to.
+#[derive(Debug, Serialize)]
+struct RouterDoc<'a> {
+    id: NodeId,
+    name: &'a str,
+    management_address: &'a str,
+    vrf: Option<&'a str>,
+    profile: &'a str,
+    system: &'a SystemInfo,
+    fingerprint: &'a Fingerprint,
+    addresses: &'a [IpAddress],
+    routes: &'a [CidrEntry],
+    interfaces: &'a [Interface],
+    neighbors: &'a [Neighbor],
+    arp: &'a [ArpEntry],
+    bridge: &'a Bridge,
+    bgp: &'a Bgp,
+    ospf: &'a Ospf,
+    inventory: &'a [PhysicalEntity],
+    health: &'a Health,
+    vrfs: &'a [Vrf],
+    wireless: &'a [WirelessLink],
+    traffic: &'a BTreeMap<i32, InterfaceRates>,
+    extra_tables: &'a BTreeMap<String, Vec<(String, String)>>,
+    /// The route used to find the parent
+    upstream: Option<&'a UpstreamRoute>,
+    /// How that route's next hop was recursively resolved
+    resolution: Option<&'a NextHopResolution>,
+}
+
+#[derive(Debug, Serialize)]
+struct ParentDoc<'a> {
+    child: NodeId,
+    parent: NodeId,
+    confidence: f32,
+    /// The parent used when drawing the tree
+    preferred: bool,
+    evidence: &'a [Evidence],
+}
+
+impl<'a> Document<'a> {
+    fn new(topology: &'a Topology) -> Self {
+        let routers = topology
+            .nodes
+            .iter()
+            .map(|node| {
+                let router = &node.router;
+                RouterDoc {
+                    id: node.id,
+                    name: &node.name,
+                    management_address: &router.connection.snmp_address,
+                    vrf: router.connection.vrf.as_deref(),
+                    profile: &router.profile,
+                    system: &router.system_info,
+                    fingerprint: &router.fingerprint,
+                    addresses: &router.ip_table.ips,
+                    routes: &router.ip_routes.routes,
+                    interfaces: &router.interfaces.interfaces,
+                    neighbors: &router.neighbors.neighbors,
+                    arp: &router.arp_table.entries,
+                    bridge: &router.bridge,
+                    bgp: &router.bgp,
+                    ospf: &router.ospf,
+                    inventory: &router.inventory.entities,
+                    health: &router.health,
+                    vrfs: &router.vrfs.vrfs,
+                    wireless: &router.wireless.links,
+                    traffic: &router.traffic.rates,
+                    extra_tables: &router.extra_tables,
+                    upstream: node.upstream.as_ref(),
+                    resolution: node.resolution.as_ref(),
+                }
+            })
+            .collect();
+        let parents = topology
+            .edges
+            .iter()
+            .filter(|e| e.kind == EdgeKind::Parent)
+            .map(|e| ParentDoc {
+                child: e.from,
+                parent: e.to,
+                confidence: e.confidence,
+                preferred: topology.preferred_parent(e.from) == Some(e.to),
+                evidence: &e.evidence,
+            })
+            .collect();
+        Self {
+            schema_version: SCHEMA_VERSION,
+            generator: format!("{} {}", env!("CARGO_PKG_NAME"), env!("CARGO_PKG_VERSION")),
+            routers,
+            parents,
+            edges: topology
+                .edges
+                .iter()
+                .filter(|e| e.kind != EdgeKind::Parent)
+                .collect(),
+            links: &topology.links,
+            findings: &topology.findings,
+            unlisted_devices: &topology.unlisted_devices,
+        }
+    }
+}
+
+/// Write the topology as a versioned JSON document.
+pub fn write_json(topology: &Topology, writer: impl Write) -> Result<()> {
+    serde_json::to_writer_pretty(writer, &Document::new(topology))?;
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::router::fixtures::{route, router};
+
+    #[test]
+    fn test_json_document() {
+        let topology = Topology::build(vec![
+            router("core", &["10.0.0.1/30"], vec![]),
+            router(
+                "agg",
+                &["10.0.0.2/30"],
+                vec![route("0.0.0.0", 0, "10.0.0.1")],
+            ),
+        ]);
+        let mut buffer = Vec::new();
+        write_json(&topology, &mut buffer).unwrap();
+        let text = String::from_utf8(buffer).unwrap();
+        let json: serde_json::Value = serde_json::from_str(&text).unwrap();
+
+        assert_eq!(json["schema_version"], SCHEMA_VERSION);
+        assert_eq!(json["routers"][1]["name"], "agg");
+        assert_eq!(json["routers"][1]["addresses"][0]["address"], "10.0.0.2");
+        assert_eq!(json["parents"][0]["child"], 1);
+        assert_eq!(json["parents"][0]["parent"], 0);
+        assert_eq!(json["parents"][0]["preferred"], true);
+        assert_eq!(json["parents"][0]["evidence"][0]["type"], "UpstreamRoute");
+        // Credentials never leave the tool
+        assert!(!text.contains("snmp_community"));
+    }
+}
@@ -0,0 +1,91 @@
+//! Files for LibreQoS, which shapes customer traffic along the network's
+//! hierarchy.
+use crate::topology::Node;
+use ipnetwork::IpNetwork;
+use serde::Deserialize;
+use std::collections::BTreeMap;
+mod network;
+mod shaped_devices;
+pub use network::write_network_json;
+pub use shaped_devices::write_shaped_devices_csv;
+
+/// The `[libreqos]` section of `router_mapper.toml`.
+#[derive(Debug, Deserialize)]
+#[serde(default)]
+pub struct LibreQosConfig {
+    /// If set, write the router hierarchy as LibreQoS's `network.json`
+    pub network_json: Option<String>,
+    /// Used for nodes whose uplink speed isn't known (including roots)
+    pub default_bandwidth_mbps: u64,
+    /// Bandwidth for named nodes, instead of their uplink speed
+    pub bandwidth: BTreeMap<String, Bandwidth>,
+    /// If set, write customer devices and prefixes as LibreQoS's
+    /// `ShapedDevices.csv`
+    pub shaped_devices_csv: Option<String>,
+    /// Only these prefixes are customers (everything, if empty)
+    pub include_prefixes: Vec<IpNetwork>,
+    /// Never shape anything inside these prefixes
+    pub exclude_prefixes: Vec<IpNetwork>,
+    /// The rates every shaped device gets
+    pub default_plan: Plan,
+}
+
+impl Default for LibreQosConfig {
+    fn default() -> Self {
+        Self {
+            network_json: None,
+            default_bandwidth_mbps: 1000,
+            bandwidth: BTreeMap::new(),
+            shaped_devices_csv: None,
+            include_prefixes: Vec::new(),
+            exclude_prefixes: Vec::new(),
+            default_plan: Plan::default(),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Deserialize)]
+pub struct Bandwidth {
+    pub download_mbps: u64,
+    pub upload_mbps: u64,
+}
+
+impl Bandwidth {
+    fn symmetric(mbps: u64) -> Self {
+        Self {
+            download_mbps: mbps,
+            upload_mbps: mbps,
+        }
+    }
+}
+
+/// A customer's guaranteed (min) and ceiling (max) rates.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Deserialize)]
+#[serde(default)]
+pub struct Plan {
+    pub download_min_mbps: u64,
+    pub upload_min_mbps: u64,
+    pub download_max_mbps: u64,
+    pub upload_max_mbps: u64,
+}
+
+impl Default for Plan {
+    fn default() -> Self {
+        Self {
+            download_min_mbps: 10,
+            upload_min_mbps: 2,
+            download_max_mbps: 100,
+            upload_max_mbps: 20,
+        }
+    }
+}
+
+/// The name a node goes by in LibreQoS: its hostname, or its management
+/// address if it didn't report one.
+fn node_name(node: &Node) -> &str {
+    if node.name.is_empty() {
+        &node.router.connection.snmp_address
+    } else {
+        &node.name
+    }
+}
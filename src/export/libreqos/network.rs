@@ -0,0 +1,233 @@
+use super::{node_name, Bandwidth, LibreQosConfig};
+use crate::topology::{EdgeKind, NodeId, Topology};
+use anyhow::{bail, Result};
+use serde::Serialize;
+use std::collections::{BTreeMap, HashMap};
+
+/// A node of LibreQoS's `network.json`: a site or access point, its
+/// bandwidth limits, and the nodes beneath it.
+#[derive(Debug, PartialEq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct NetworkNode {
+    pub download_bandwidth_mbps: u64,
+    pub upload_bandwidth_mbps: u64,
+    #[serde(skip_serializing_if = "BTreeMap::is_empty")]
+    pub children: BTreeMap<String, NetworkNode>,
+}
+
+/// The top level of `network.json`: one entry per root of the hierarchy.
+pub type Network = BTreeMap<String, NetworkNode>;
+
+/// Convert the preferred-parent hierarchy into LibreQoS's nested format.
+/// Fails if two nodes share a name (LibreQoS keys nodes by name) or if
+/// the hierarchy has a cycle (its nodes would have no place in the tree).
+pub fn build_network(topology: &Topology, config: &LibreQosConfig) -> Result<Network> {
+    check_unique_names(topology)?;
+    check_no_cycles(topology)?;
+    Ok(topology
+        .roots()
+        .into_iter()
+        .map(|root| network_node(topology, config, root))
+        .collect())
+}
+
+/// Write `network.json` for LibreQoS.
+pub fn write_network_json(
+    topology: &Topology,
+    config: &LibreQosConfig,
+    filename: &str,
+) -> Result<()> {
+    let network = build_network(topology, config)?;
+    let file = std::fs::File::create(filename)?;
+    serde_json::to_writer_pretty(file, &network)?;
+    Ok(())
+}
+
+fn network_node(
+    topology: &Topology,
+    config: &LibreQosConfig,
+    node: NodeId,
+) -> (String, NetworkNode) {
+    let bandwidth = bandwidth(topology, config, node);
+    let children = topology
+        .children(node)
+        .into_iter()
+        .map(|child| network_node(topology, config, child))
+        .collect();
+    (
+        node_name(&topology.nodes[node]).to_string(),
+        NetworkNode {
+            download_bandwidth_mbps: bandwidth.download_mbps,
+            upload_bandwidth_mbps: bandwidth.upload_mbps,
+            children,
+        },
+    )
+}
+
+/// A node's bandwidth: a configured override, else the capacity of the
+/// radio link to its parent, else its uplink interface's speed, else the
+/// configured default.
+fn bandwidth(topology: &Topology, config: &LibreQosConfig, node: NodeId) -> Bandwidth {
+    if let Some(bandwidth) = config.bandwidth.get(node_name(&topology.nodes[node])) {
+        return *bandwidth;
+    }
+    if let Some(bandwidth) = radio_capacity(topology, node) {
+        return bandwidth;
+    }
+    let speed = topology
+        .upstream_interface(node)
+        .map(|i| i.speed_mbps)
+        .filter(|speed| *speed > 0)
+        .unwrap_or(config.default_bandwidth_mbps);
+    Bandwidth::symmetric(speed)
+}
+
+/// What the radio link between `node` and its preferred parent can carry,
+/// seen from `node`: it downloads what it receives.
+fn radio_capacity(topology: &Topology, node: NodeId) -> Option<Bandwidth> {
+    let parent = topology.preferred_parent(node)?;
+    let edge = topology
+        .edges_of(node)
+        .find(|e| e.kind == EdgeKind::Wireless && e.connects(node, parent))?;
+    let link = edge.wireless()?;
+    let (tx, rx) = if edge.from == node {
+        link.capacity_mbps()
+    } else {
+        link.reversed().capacity_mbps()
+    };
+    // Less than 1 Mbps is as good as no figure at all
+    let mbps = |capacity: Option<f64>| Some(capacity? as u64).filter(|m| *m > 0);
+    Some(Bandwidth {
+        download_mbps: mbps(rx)?,
+        upload_mbps: mbps(tx)?,
+    })
+}
+
+fn check_unique_names(topology: &Topology) -> Result<()> {
+    let mut seen: HashMap<&str, NodeId> = HashMap::new();
+    for node in topology.nodes.iter() {
+        let name = node_name(node);
+        if let Some(other) = seen.insert(name, node.id) {
+            bail!(
+                "Routers {} and {} are both called {name}; LibreQoS needs unique node names",
+                topology.nodes[other].router.connection.snmp_address,
+                node.router.connection.snmp_address
+            );
+        }
+    }
+    Ok(())
+}
+
+fn check_no_cycles(topology: &Topology) -> Result<()> {
+    for node in topology.nodes.iter() {
+        let path = topology.path_to_root(node.id);
+        let last = *path.last().unwrap();
+        // The walk up stopped short of a root, so it came back on itself
+        if let Some(repeat) = topology.preferred_parent(last) {
+            let start = path.iter().position(|n| *n == repeat).unwrap();
+            let names: Vec<_> = path[start..]
+                .iter()
+                .map(|n| node_name(&topology.nodes[*n]))
+                .collect();
+            bail!(
+                "The router hierarchy has a cycle ({}); it can't be written as network.json",
+                names.join(" -> ")
+            );
+        }
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::router::{
+        fixtures::{route, router},
+        Interface, WirelessLink,
+    };
+    use crate::topology::Evidence;
+
+    // core <- agg <- edge
+    fn test_topology() -> Topology {
+        let mut agg = router(
+            "agg",
+            &["10.0.0.2/30", "10.0.1.1/29"],
+            vec![route("0.0.0.0", 0, "10.0.0.1")],
+        );
+        agg.interfaces.interfaces.push(Interface {
+            index: 1,
+            speed_mbps: 10_000,
+            ..Default::default()
+        });
+        Topology::build(vec![
+            router("core", &["10.0.0.1/30"], vec![]),
+            agg,
+            router(
+                "edge",
+                &["10.0.1.2/29"],
+                vec![route("0.0.0.0", 0, "10.0.1.1")],
+            ),
+        ])
+    }
+
+    #[test]
+    fn test_network_json() {
+        let topology = test_topology();
+        let mut config = LibreQosConfig::default();
+        config.bandwidth.insert(
+            "edge".to_string(),
+            Bandwidth {
+                download_mbps: 500,
+                upload_mbps: 100,
+            },
+        );
+        let network = build_network(&topology, &config).unwrap();
+        let core = &network["core"];
+        assert_eq!(core.download_bandwidth_mbps, 1000);
+        let agg = &core.children["agg"];
+        assert_eq!(agg.download_bandwidth_mbps, 10_000);
+        let edge = &agg.children["edge"];
+        assert_eq!(edge.download_bandwidth_mbps, 500);
+        assert_eq!(edge.upload_bandwidth_mbps, 100);
+
+        let json = serde_json::to_value(&network).unwrap();
+        assert_eq!(
+            json["core"]["children"]["agg"]["uploadBandwidthMbps"],
+            10_000
+        );
+        assert!(json["core"]["children"]["agg"]["children"]["edge"]
+            .get("children")
+            .is_none());
+    }
+
+    #[test]
+    fn test_unusable_radio_capacity() {
+        let mut topology = test_topology();
+        let link = WirelessLink {
+            tx_capacity_mbps: Some(0.0),
+            rx_capacity_mbps: Some(f64::NAN),
+            ..Default::default()
+        };
+        topology.add_edge(1, 0, EdgeKind::Wireless, 0.9, Evidence::Wireless { link });
+        assert_eq!(radio_capacity(&topology, 1), None);
+        // Falls through to the uplink's interface speed
+        let network = build_network(&topology, &LibreQosConfig::default()).unwrap();
+        assert_eq!(
+            network["core"].children["agg"].download_bandwidth_mbps,
+            10_000
+        );
+    }
+
+    #[test]
+    fn test_network_validation() {
+        let mut topology = test_topology();
+        topology.nodes[2].name = "agg".to_string();
+        assert!(build_network(&topology, &LibreQosConfig::default()).is_err());
+
+        let mut topology = test_topology();
+        let evidence = topology.edges[0].evidence[0].clone();
+        topology.add_edge(0, 2, EdgeKind::Parent, 0.9, evidence);
+        let error = build_network(&topology, &LibreQosConfig::default()).unwrap_err();
+        assert!(error.to_string().contains("cycle"));
+    }
+}
@@ -0,0 +1,379 @@
+use super::{node_name, LibreQosConfig, Plan};
+use crate::topology::{DiscoveryReason, NodeId, Topology};
+use anyhow::Result;
+use ipnetwork::IpNetwork;
+use serde::Serialize;
+use std::{
+    collections::{BTreeMap, HashSet},
+    net::IpAddr,
+};
+
+/// A customer device (or routed customer prefix) and the router node it
+/// hangs off.
+#[derive(Debug, Clone, PartialEq)]
+pub struct ShapedDevice {
+    pub parent: NodeId,
+    /// Empty for prefixes only known from a route
+    pub mac: String,
+    /// The device's address, then any prefixes routed through it
+    pub prefixes: Vec<IpNetwork>,
+    /// Where the device was found
+    pub comment: String,
+}
+
+/// One line of LibreQoS's `ShapedDevices.csv`.
+#[derive(Debug, Serialize)]
+struct ShapedDeviceRow<'a> {
+    #[serde(rename = "Circuit ID")]
+    circuit_id: &'a str,
+    #[serde(rename = "Circuit Name")]
+    circuit_name: &'a str,
+    #[serde(rename = "Device ID")]
+    device_id: &'a str,
+    #[serde(rename = "Device Name")]
+    device_name: &'a str,
+    #[serde(rename = "Parent Node")]
+    parent_node: &'a str,
+    #[serde(rename = "MAC")]
+    mac: &'a str,
+    #[serde(rename = "IPv4")]
+    ipv4: String,
+    #[serde(rename = "IPv6")]
+    ipv6: String,
+    #[serde(rename = "Download Min Mbps")]
+    download_min_mbps: u64,
+    #[serde(rename = "Upload Min Mbps")]
+    upload_min_mbps: u64,
+    #[serde(rename = "Download Max Mbps")]
+    download_max_mbps: u64,
+    #[serde(rename = "Upload Max Mbps")]
+    upload_max_mbps: u64,
+    #[serde(rename = "Comment")]
+    comment: &'a str,
+}
+
+/// Is `inner` entirely inside `outer`?
+fn covers(outer: &IpNetwork, inner: &IpNetwork) -> bool {
+    outer.prefix() <= inner.prefix() && outer.contains(inner.network())
+}
+
+/// What we know about the network itself, gathered once so that each ARP
+/// entry and route can be checked against it cheaply.
+struct Infrastructure {
+    /// Every polled router's addresses
+    addresses: HashSet<IpAddr>,
+    /// Every polled router's interface MACs, in upper case
+    macs: HashSet<String>,
+    /// Unlisted devices that announce themselves with LLDP/CDP/MNDP, which
+    /// are network gear rather than customers
+    gear: HashSet<IpAddr>,
+    /// Length of each node's path to the root; deeper is nearer the edge
+    depth: Vec<usize>,
+}
+
+impl Infrastructure {
+    fn new(topology: &Topology) -> Self {
+        let routers = || topology.nodes.iter().map(|n| &n.router);
+        Self {
+            addresses: routers()
+                .flat_map(|r| r.ip_table.ips.iter().map(|ip| ip.address))
+                .collect(),
+            macs: routers()
+                .flat_map(|r| r.interfaces.interfaces.iter())
+                .filter(|i| !i.phys_address.is_empty())
+                .map(|i| i.phys_address.to_uppercase())
+                .collect(),
+            gear: topology
+                .unlisted_devices
+                .iter()
+                .filter(|d| matches!(d.reason, DiscoveryReason::Neighbor(_)))
+                .map(|d| d.address)
+                .collect(),
+            depth: topology
+                .nodes
+                .iter()
+                .map(|n| topology.path_to_root(n.id).len())
+                .collect(),
+        }
+    }
+
+    /// Does `network` hold any polled router's address?
+    fn holds_router(&self, network: &IpNetwork) -> bool {
+        if network.prefix() == host_prefix(network) {
+            self.addresses.contains(&network.ip())
+        } else {
+            self.addresses.iter().any(|a| network.contains(*a))
+        }
+    }
+
+    /// Should `network` be shaped? It has to be inside an included prefix
+    /// (if any are configured), outside every excluded prefix, and must not
+    /// hold any polled router's address.
+    fn is_customer(&self, config: &LibreQosConfig, network: &IpNetwork) -> bool {
+        let included = config.include_prefixes.is_empty()
+            || config.include_prefixes.iter().any(|p| covers(p, network));
+        let excluded = config.exclude_prefixes.iter().any(|p| covers(p, network));
+        included && !excluded && !self.holds_router(network)
+    }
+}
+
+/// Can traffic to `address` be shaped at all? Link-local, multicast and
+/// unspecified addresses never carry a customer's traffic.
+fn is_shapeable(address: IpAddr) -> bool {
+    match address {
+        IpAddr::V4(v4) => {
+            !(v4.is_unspecified() || v4.is_multicast() || v4.is_link_local() || v4.is_broadcast())
+        }
+        IpAddr::V6(v6) => !(v6.is_unspecified() || v6.is_multicast() || v6.is_unicast_link_local()),
+    }
+}
+
+/// Find customer devices and prefixes, each assigned to the nearest
+/// router: the deepest node in the tree that has the device in its ARP
+/// cache, or that routes the prefix to a directly connected next hop.
+/// Addresses with the same MAC under the same router (a dual-stack CPE's
+/// IPv4 and IPv6 addresses) are one device, and prefixes routed through a
+/// device found in ARP are added to it.
+pub fn shaped_devices(topology: &Topology, config: &LibreQosConfig) -> Vec<ShapedDevice> {
+    let infrastructure = Infrastructure::new(topology);
+    let depth = |node: NodeId| infrastructure.depth[node];
+
+    // The nearest router and MAC for each address seen in ARP
+    let mut nearest: BTreeMap<IpAddr, (NodeId, String)> = BTreeMap::new();
+    for node in topology.nodes.iter() {
+        for entry in node.router.arp_table.neighbors() {
+            let mac = entry.mac.to_uppercase();
+            if !is_shapeable(entry.address)
+                || !infrastructure.is_customer(config, &IpNetwork::from(entry.address))
+                || infrastructure.gear.contains(&entry.address)
+                || infrastructure.macs.contains(&mac)
+            {
+                continue;
+            }
+            match nearest.get(&entry.address) {
+                Some((existing, _)) if depth(*existing) >= depth(node.id) => {}
+                _ => {
+                    nearest.insert(entry.address, (node.id, mac));
+                }
+            }
+        }
+    }
+
+    // Keyed by (parent, MAC); IPv4 addresses sort first, so they lead
+    let mut hosts: BTreeMap<(NodeId, String), ShapedDevice> = BTreeMap::new();
+    let mut host_of: BTreeMap<IpAddr, (NodeId, String)> = BTreeMap::new();
+    for (address, (parent, mac)) in nearest {
+        hosts
+            .entry((parent, mac.clone()))
+            .or_insert_with(|| ShapedDevice {
+                parent,
+                mac: mac.clone(),
+                prefixes: Vec::new(),
+                comment: format!("ARP on {}", node_name(&topology.nodes[parent])),
+            })
+            .prefixes
+            .push(IpNetwork::from(address));
+        host_of.insert(address, (parent, mac));
+    }
+
+    // Routes to customer prefixes, kept only on routers where the next hop
+    // is directly attached (or the route is connected, as PPPoE sessions are)
+    let mut routed: BTreeMap<IpNetwork, (NodeId, IpAddr)> = BTreeMap::new();
+    for node in topology.nodes.iter() {
+        for route in node.router.ip_routes.routes.iter() {
+            let Some(network) = route.ip_network() else {
+                continue;
+            };
+            if network.prefix() == 0
+                || !is_shapeable(network.ip())
+                || !infrastructure.is_customer(config, &network)
+            {
+                continue;
+            }
+            let attached = route.next_hop.is_unspecified()
+                || (node.router.is_connected(route.next_hop)
+                    && !infrastructure.addresses.contains(&route.next_hop));
+            if !attached {
+                continue;
+            }
+            match routed.get(&network) {
+                Some((existing, _)) if depth(*existing) >= depth(node.id) => {}
+                _ => {
+                    routed.insert(network, (node.id, route.next_hop));
+                }
+            }
+        }
+    }
+
+    let mut devices = Vec::new();
+    for (network, (parent, next_hop)) in routed {
+        if host_of.contains_key(&network.ip()) && network.prefix() == host_prefix(&network) {
+            // A host route to a device we already have
+            continue;
+        }
+        if let Some(host) = host_of.get(&next_hop).and_then(|key| hosts.get_mut(key)) {
+            host.prefixes.push(network);
+            continue;
+        }
+        let comment = if next_hop.is_unspecified() {
+            format!("Connected route on {}", node_name(&topology.nodes[parent]))
+        } else {
+            format!(
+                "Routed on {} via {next_hop}",
+                node_name(&topology.nodes[parent])
+            )
+        };
+        devices.push(ShapedDevice {
+            parent,
+            mac: String::new(),
+            prefixes: vec![network],
+            comment,
+        });
+    }
+    devices.extend(hosts.into_values());
+    devices.sort_by(|a, b| (a.parent, a.prefixes[0]).cmp(&(b.parent, b.prefixes[0])));
+    devices
+}
+
+fn host_prefix(network: &IpNetwork) -> u8 {
+    match network {
+        IpNetwork::V4(_) => 32,
+        IpNetwork::V6(_) => 128,
+    }
+}
+
+/// A prefix as LibreQoS expects it: a bare address for a single host.
+fn format_prefix(network: &IpNetwork) -> String {
+    if network.prefix() == host_prefix(network) {
+        network.ip().to_string()
+    } else {
+        network.to_string()
+    }
+}
+
+fn join_prefixes<'a>(prefixes: impl Iterator<Item = &'a IpNetwork>) -> String {
+    prefixes.map(format_prefix).collect::<Vec<_>>().join(", ")
+}
+
+/// Write `ShapedDevices.csv` for LibreQoS, with every device on the
+/// default plan.
+pub fn write_shaped_devices_csv(
+    topology: &Topology,
+    config: &LibreQosConfig,
+    filename: &str,
+) -> Result<()> {
+    let Plan {
+        download_min_mbps,
+        upload_min_mbps,
+        download_max_mbps,
+        upload_max_mbps,
+    } = config.default_plan;
+    let mut writer = csv::Writer::from_path(filename)?;
+    for device in shaped_devices(topology, config) {
+        let id = format_prefix(&device.prefixes[0]);
+        writer.serialize(ShapedDeviceRow {
+            circuit_id: &id,
+            circuit_name: &id,
+            device_id: &id,
+            device_name: &id,
+            parent_node: node_name(&topology.nodes[device.parent]),
+            mac: &device.mac,
+            ipv4: join_prefixes(device.prefixes.iter().filter(|p| p.is_ipv4())),
+            ipv6: join_prefixes(device.prefixes.iter().filter(|p| p.is_ipv6())),
+            download_min_mbps,
+            upload_min_mbps,
+            download_max_mbps,
+            upload_max_mbps,
+            comment: &device.comment,
+        })?;
+    }
+    writer.flush()?;
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::router::fixtures::{arp, route, router};
+
+    // core <- agg, with customers on agg's 100.64.1.0/24
+    fn test_topology() -> Topology {
+        let mut core = router("core", &["10.0.0.1/30", "100.64.1.254/24"], vec![]);
+        let mut agg = router(
+            "agg",
+            &["10.0.0.2/30", "100.64.1.1/24"],
+            vec![
+                route("0.0.0.0", 0, "10.0.0.1"),
+                // A customer's routed block, behind their CPE
+                route("192.0.2.0", 29, "100.64.1.10"),
+                // A PPPoE session
+                route("100.64.9.9", 32, "0.0.0.0"),
+            ],
+        );
+        agg.arp_table.entries = vec![
+            arp("100.64.1.10", "00:11:22:33:44:55"),
+            arp("100.64.1.11", "00:11:22:33:44:66"),
+            arp("10.0.0.1", "00:AA:00:00:00:01"),
+        ];
+        // The core sees a customer on the same LAN, but agg is nearer
+        core.arp_table.entries = vec![arp("100.64.1.11", "00:11:22:33:44:66")];
+        core.ip_routes
+            .routes
+            .push(route("192.0.2.0", 29, "10.0.0.2"));
+        Topology::build(vec![core, agg])
+    }
+
+    #[test]
+    fn test_shaped_devices() {
+        let topology = test_topology();
+        let devices = shaped_devices(&topology, &LibreQosConfig::default());
+        assert_eq!(devices.len(), 3);
+        assert!(devices.iter().all(|d| d.parent == 1));
+
+        let cpe = devices
+            .iter()
+            .find(|d| d.mac == "00:11:22:33:44:55")
+            .unwrap();
+        assert_eq!(
+            join_prefixes(cpe.prefixes.iter()),
+            "100.64.1.10, 192.0.2.0/29"
+        );
+        let pppoe = devices.iter().find(|d| d.mac.is_empty()).unwrap();
+        assert_eq!(pppoe.comment, "Connected route on agg");
+    }
+
+    #[test]
+    fn test_shaped_device_prefix_filters() {
+        let topology = test_topology();
+        let config = LibreQosConfig {
+            include_prefixes: vec!["100.64.0.0/10".parse().unwrap()],
+            exclude_prefixes: vec!["100.64.9.0/24".parse().unwrap()],
+            ..Default::default()
+        };
+        let devices = shaped_devices(&topology, &config);
+        let addresses: Vec<_> = devices
+            .iter()
+            .map(|d| join_prefixes(d.prefixes.iter()))
+            .collect();
+        assert_eq!(addresses, vec!["100.64.1.10", "100.64.1.11"]);
+    }
+
+    #[test]
+    fn test_dual_stack_cpe() {
+        let mut agg = router("agg", &["100.64.1.1/24", "2001:db8:1::1/64"], vec![]);
+        agg.arp_table.entries = vec![
+            arp("100.64.1.10", "00:11:22:33:44:55"),
+            arp("2001:db8:1::10", "00:11:22:33:44:55"),
+            // The same CPE's link-local address isn't worth shaping
+            arp("fe80::211:22ff:fe33:4455", "00:11:22:33:44:55"),
+            arp("224.0.0.251", "01:00:5E:00:00:FB"),
+        ];
+        let topology = Topology::build(vec![agg]);
+        let devices = shaped_devices(&topology, &LibreQosConfig::default());
+        assert_eq!(devices.len(), 1);
+        assert_eq!(
+            join_prefixes(devices[0].prefixes.iter()),
+            "100.64.1.10, 2001:db8:1::10"
+        );
+    }
+}
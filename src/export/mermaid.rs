@@ -0,0 +1,114 @@
+use super::graph::{EdgeStyle, Graph, GraphNode};
+use crate::topology::Topology;
+use anyhow::Result;
+use std::io::Write;
+
+/// Replace the characters Mermaid treats as markup with its `#...;` entity
+/// codes. `#` goes first so the codes themselves aren't escaped again.
+fn escape(text: &str) -> String {
+    text.replace('#', "#35;")
+        .replace('"', "#quot;")
+        .replace('<', "#lt;")
+        .replace('>', "#gt;")
+        .replace('|', "#124;")
+}
+
+fn quote(text: &str) -> String {
+    format!("\"{}\"", escape(text))
+}
+
+/// A quoted Mermaid label, one line per entry.
+fn label(lines: &[String]) -> String {
+    let lines: Vec<String> = lines.iter().map(|line| escape(line)).collect();
+    format!("\"{}\"", lines.join("<br/>"))
+}
+
+fn node(node: &GraphNode) -> String {
+    format!("n{}[{}]", node.id, label(&node.lines))
+}
+
+/// Write the topology as a Mermaid flowchart, with each site as a
+/// subgraph. Parents confirmed by a neighbor protocol or OSPF are thick
+/// arrows, those inferred from routes alone are dotted arrows, and other
+/// neighbor/OSPF links are grey dotted lines.
+pub fn write_mermaid(topology: &Topology, mut writer: impl Write) -> Result<()> {
+    let graph = Graph::new(topology);
+    writeln!(writer, "flowchart TD")?;
+    for (index, (site, nodes)) in graph.sites.iter().enumerate() {
+        writeln!(writer, "    subgraph site{index}[{}]", quote(site))?;
+        for id in nodes {
+            writeln!(writer, "        {}", node(&graph.nodes[*id]))?;
+        }
+        writeln!(writer, "    end")?;
+    }
+    for unsited in graph.unsited() {
+        writeln!(writer, "    {}", node(unsited))?;
+    }
+    let mut peers = Vec::new();
+    for (index, edge) in graph.edges.iter().enumerate() {
+        let arrow = match edge.style {
+            EdgeStyle::Confirmed => "==>",
+            EdgeStyle::Inferred => "-.->",
+            EdgeStyle::Peer => {
+                peers.push(index.to_string());
+                "-.-"
+            }
+        };
+        if edge.lines.is_empty() {
+            writeln!(writer, "    n{} {arrow} n{}", edge.from, edge.to)?;
+        } else {
+            writeln!(
+                writer,
+                "    n{} {arrow}|{}| n{}",
+                edge.from,
+                label(&edge.lines),
+                edge.to
+            )?;
+        }
+    }
+    if !peers.is_empty() {
+        writeln!(writer, "    linkStyle {} stroke:#999", peers.join(","))?;
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::router::fixtures::{route, router};
+
+    #[test]
+    fn test_mermaid() {
+        let mut core = router("core", &["10.0.0.1/30"], vec![]);
+        core.connection.site = Some("Main POP".to_string());
+        let topology = Topology::build(vec![
+            core,
+            router(
+                "agg",
+                &["10.0.0.2/30"],
+                vec![route("0.0.0.0", 0, "10.0.0.1")],
+            ),
+        ]);
+        let mut buffer = Vec::new();
+        write_mermaid(&topology, &mut buffer).unwrap();
+        let mermaid = String::from_utf8(buffer).unwrap();
+        assert_eq!(
+            mermaid,
+            "flowchart TD\n    subgraph site0[\"Main POP\"]\n        n0[\"core<br/>10.0.0.1\"]\n    end\n    n1[\"agg<br/>10.0.0.2\"]\n    n0 -.->|\"10.0.0.1\"| n1\n"
+        );
+    }
+
+    #[test]
+    fn test_mermaid_escaping() {
+        let mut edge = router("<b>edge</b> \"#1\" |x|", &["10.0.0.1/30"], vec![]);
+        edge.connection.site = Some("A|B".to_string());
+        let topology = Topology::build(vec![edge]);
+        let mut buffer = Vec::new();
+        write_mermaid(&topology, &mut buffer).unwrap();
+        let mermaid = String::from_utf8(buffer).unwrap();
+        assert!(mermaid.contains("subgraph site0[\"A#124;B\"]"));
+        assert!(mermaid.contains(
+            "n0[\"#lt;b#gt;edge#lt;/b#gt; #quot;#35;1#quot; #124;x#124;<br/>10.0.0.1\"]"
+        ));
+    }
+}
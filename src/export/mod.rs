@@ -0,0 +1,16 @@
+//! Writers that turn a `Topology` into files for other tools.
+mod dot;
+mod graph;
+mod health;
+mod html;
+mod inventory;
+mod json;
+mod libreqos;
+mod mermaid;
+pub use dot::write_dot;
+pub use health::write_health_csv;
+pub use html::write_html;
+pub use inventory::write_inventory_csv;
+pub use json::write_json;
+pub use libreqos::{write_network_json, write_shaped_devices_csv, LibreQosConfig};
+pub use mermaid::write_mermaid;
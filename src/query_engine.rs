@@ -1,41 +1,111 @@
 use crate::csnmp::{ObjectIdentifier, ObjectValue, Snmp2cClient};
 use anyhow::{bail, Result};
 use ipnetwork::ip_mask_to_prefix;
+use once_cell::sync::Lazy;
 use std::{
+    collections::HashMap,
     net::{IpAddr, SocketAddr},
+    sync::Mutex,
     time::Duration,
 };
 
 // You may want to adjust this timeout depending on your network
-const TIMEOUT: Duration = Duration::from_secs(5);
+pub const TIMEOUT: Duration = Duration::from_secs(5);
 
 // Setting this to large numbers breaks on Mikrotik. Higher numbers
 // give a performance benefit on platforms that support it. 20 seems
 // to be a safe defalt.
-const MAX_REPEAT: u32 = 20;
+pub const MAX_REPEAT: u32 = 20;
+
+/// How to query one target, set from its vendor profile once the device
+/// has been identified.
+#[derive(Debug, Clone)]
+pub struct QuerySettings {
+    pub max_repetitions: u32,
+    pub timeout: Duration,
+    /// (standard OID prefix, where this device actually keeps it)
+    pub oid_fixups: Vec<(String, String)>,
+}
+
+impl Default for QuerySettings {
+    fn default() -> Self {
+        Self {
+            max_repetitions: MAX_REPEAT,
+            timeout: TIMEOUT,
+            oid_fixups: Vec::new(),
+        }
+    }
+}
+
+static TARGET_SETTINGS: Lazy<Mutex<HashMap<String, QuerySettings>>> =
+    Lazy::new(|| Mutex::new(HashMap::new()));
+
+/// Use `settings` for all further requests to `target_ip`.
+pub fn set_query_settings(target_ip: &str, settings: QuerySettings) {
+    TARGET_SETTINGS
+        .lock()
+        .unwrap()
+        .insert(target_ip.to_string(), settings);
+}
+
+fn query_settings(target_ip: &str) -> QuerySettings {
+    TARGET_SETTINGS
+        .lock()
+        .unwrap()
+        .get(target_ip)
+        .cloned()
+        .unwrap_or_default()
+}
+
+/// Replace `from` with `to` at the start of `oid`, on a sub-identifier
+/// boundary.
+fn replace_prefix(oid: &str, from: &str, to: &str) -> Option<String> {
+    let rest = oid.strip_prefix(from)?;
+    if rest.is_empty() || rest.starts_with('.') {
+        Some(format!("{to}{rest}"))
+    } else {
+        None
+    }
+}
 
 pub async fn snmp_query(
     target_ip: &str,
     community: &str,
     oid: &str,
 ) -> Result<Vec<(String, ObjectValue)>> {
+    let settings = query_settings(target_ip);
+    // Walk the device's own location for a relocated table, and report
+    // the results under the standard OIDs the loaders expect
+    let fixup = settings.oid_fixups.iter().find_map(|(standard, actual)| {
+        replace_prefix(oid, standard, actual).map(|walk| (walk, standard, actual))
+    });
+    let walk_oid = fixup.as_ref().map_or(oid, |(walk, _, _)| walk.as_str());
+
     let community = Vec::from(community);
-    let top_oid: ObjectIdentifier = oid.parse()?;
+    let top_oid: ObjectIdentifier = walk_oid.parse()?;
     let sock_addr = SocketAddr::from((target_ip.parse::<IpAddr>()?, 161));
 
     let client = Snmp2cClient::new(
         sock_addr,
         community,
         Some("0.0.0.0:0".parse()?),
-        Some(TIMEOUT),
+        Some(settings.timeout),
     )
     .await?;
 
-    let results = client.walk_bulk(top_oid, MAX_REPEAT).await?;
+    let results = client.walk_bulk(top_oid, settings.max_repetitions).await?;
 
     let result = results
         .into_iter()
-        .map(|(oid, value)| (oid.to_string(), value))
+        .map(|(oid, value)| {
+            let oid = oid.to_string();
+            match &fixup {
+                Some((_, standard, actual)) => {
+                    (replace_prefix(&oid, actual, standard).unwrap_or(oid), value)
+                }
+                None => (oid, value),
+            }
+        })
         .collect();
 
     Ok(result)
@@ -72,3 +142,87 @@ pub fn as_cidr(value: &ObjectValue) -> Result<u8> {
     let ip = as_ip(value)?;
     Ok(ip_mask_to_prefix(ip)?)
 }
+
+pub fn as_oid(value: &ObjectValue) -> Result<String> {
+    match value {
+        ObjectValue::ObjectId(oid) => Ok(oid.to_string()),
+        _ => bail!("Unknown value type"),
+    }
+}
+
+pub fn as_u32(value: &ObjectValue) -> Result<u32> {
+    match value {
+        ObjectValue::Unsigned32(i) => Ok(*i),
+        ObjectValue::Counter32(i) => Ok(*i),
+        ObjectValue::TimeTicks(i) => Ok(*i),
+        ObjectValue::Integer(i) => Ok(*i as u32),
+        _ => bail!("Unknown value type"),
+    }
+}
+
+pub fn as_u64(value: &ObjectValue) -> Result<u64> {
+    match value.as_u64() {
+        Some(i) => Ok(i),
+        None => bail!("Unknown value type"),
+    }
+}
+
+pub fn as_bytes(value: &ObjectValue) -> Result<&[u8]> {
+    match value {
+        ObjectValue::String(s) => Ok(s),
+        ObjectValue::Opaque(s) => Ok(s),
+        _ => bail!("Unknown value type"),
+    }
+}
+
+/// Format an octet string (ifPhysAddress and friends) as a MAC address.
+pub fn as_mac(value: &ObjectValue) -> Result<String> {
+    Ok(format_mac(as_bytes(value)?))
+}
+
+pub fn format_mac(bytes: &[u8]) -> String {
+    bytes
+        .iter()
+        .map(|b| format!("{b:02X}"))
+        .collect::<Vec<_>>()
+        .join(":")
+}
+
+/// The last sub-identifier of an OID, which is the row index for most
+/// simple tables.
+pub fn last_index(oid: &str) -> Result<i32> {
+    let Some((_, index)) = oid.rsplit_once('.') else {
+        bail!("OID {oid} has no index");
+    };
+    Ok(index.parse()?)
+}
+
+/// The sub-identifiers following `prefix` in `oid` (the row index of a table
+/// column), or `None` if `oid` isn't under `prefix`.
+pub fn oid_index(oid: &str, prefix: &str) -> Option<Vec<u32>> {
+    let suffix = oid.strip_prefix(prefix)?.strip_prefix('.')?;
+    suffix.split('.').map(|s| s.parse().ok()).collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_replace_prefix() {
+        assert_eq!(
+            replace_prefix(
+                "1.3.6.1.2.1.4.24.4.1.1",
+                "1.3.6.1.2.1.4.24",
+                "1.3.6.1.4.1.9.1"
+            )
+            .as_deref(),
+            Some("1.3.6.1.4.1.9.1.4.1.1")
+        );
+        // Only on a sub-identifier boundary
+        assert_eq!(
+            replace_prefix("1.3.6.1.2.1.40", "1.3.6.1.2.1.4", "1.2"),
+            None
+        );
+    }
+}
@@ -0,0 +1,172 @@
+use super::neighbors::decode_address;
+use crate::query_engine::{as_int, as_mac, oid_index, snmp_query};
+use anyhow::Result;
+use serde::Serialize;
+use std::{
+    collections::BTreeMap,
+    net::{IpAddr, Ipv4Addr},
+};
+
+// IP-MIB ipNetToPhysicalTable (IPv4 and IPv6)
+const IP_NET_TO_PHYSICAL_TABLE: &str = "1.3.6.1.2.1.4.35.1";
+// RFC1213 ipNetToMediaTable (IPv4 only)
+const IP_NET_TO_MEDIA_TABLE: &str = "1.3.6.1.2.1.4.22.1";
+
+/// ipNetToPhysicalType / ipNetToMediaType
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize)]
+pub enum ArpEntryType {
+    #[default]
+    Other,
+    Invalid,
+    Dynamic,
+    Static,
+    /// The router's own address (ipNetToPhysicalTable only)
+    Local,
+}
+
+impl From<i32> for ArpEntryType {
+    fn from(value: i32) -> Self {
+        match value {
+            2 => Self::Invalid,
+            3 => Self::Dynamic,
+            4 => Self::Static,
+            5 => Self::Local,
+            _ => Self::Other,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct ArpEntry {
+    pub address: IpAddr,
+    pub mac: String,
+    pub if_index: i32,
+    pub entry_type: ArpEntryType,
+}
+
+/// The router's ARP (IPv4) and neighbor (IPv6) caches.
+#[derive(Debug, Default, Serialize)]
+pub struct ArpTable {
+    pub entries: Vec<ArpEntry>,
+}
+
+/// Decode an `InetAddressType.length.bytes...` index fragment.
+fn inet_address(index: &[u32]) -> Option<IpAddr> {
+    let (address_type, rest) = index.split_first()?;
+    let (length, rest) = rest.split_first()?;
+    if rest.len() != *length as usize {
+        return None;
+    }
+    let bytes: Vec<u8> = rest.iter().map(|b| *b as u8).collect();
+    decode_address(*address_type, &bytes)
+}
+
+impl ArpTable {
+    /// Entries that point at real neighbors (not invalid, not our own).
+    pub fn neighbors(&self) -> impl Iterator<Item = &ArpEntry> {
+        self.entries.iter().filter(|e| {
+            !matches!(e.entry_type, ArpEntryType::Invalid | ArpEntryType::Local)
+                && !e.mac.is_empty()
+        })
+    }
+
+    pub(crate) async fn from_snmp(ip_address: &str, community: &str) -> Result<Self> {
+        let entries = match Self::from_physical_table(ip_address, community).await {
+            Ok(entries) if !entries.is_empty() => entries,
+            _ => {
+                tracing::info!("Falling back to ipNetToMediaTable for {ip_address}");
+                Self::from_media_table(ip_address, community).await?
+            }
+        };
+        Ok(Self { entries })
+    }
+
+    async fn from_physical_table(ip_address: &str, community: &str) -> Result<Vec<ArpEntry>> {
+        let table = snmp_query(ip_address, community, IP_NET_TO_PHYSICAL_TABLE).await?;
+        let mut entries: BTreeMap<(i32, IpAddr), ArpEntry> = BTreeMap::new();
+        for (oid, val) in table {
+            // column.ifIndex.addressType.length.address...
+            let Some(index) = oid_index(&oid, IP_NET_TO_PHYSICAL_TABLE) else {
+                continue;
+            };
+            if index.len() < 4 {
+                continue;
+            }
+            let if_index = index[1] as i32;
+            let Some(address) = inet_address(&index[2..]) else {
+                continue;
+            };
+            let entry = entries
+                .entry((if_index, address))
+                .or_insert_with(|| ArpEntry {
+                    address,
+                    mac: String::new(),
+                    if_index,
+                    entry_type: ArpEntryType::Other,
+                });
+            match index[0] {
+                // ipNetToPhysicalPhysAddress
+                4 => entry.mac = as_mac(&val).unwrap_or_default(),
+                // ipNetToPhysicalType
+                6 => entry.entry_type = as_int(&val)?.into(),
+                _ => {}
+            }
+        }
+        Ok(entries.into_values().collect())
+    }
+
+    async fn from_media_table(ip_address: &str, community: &str) -> Result<Vec<ArpEntry>> {
+        let table = snmp_query(ip_address, community, IP_NET_TO_MEDIA_TABLE).await?;
+        let mut entries: BTreeMap<(i32, IpAddr), ArpEntry> = BTreeMap::new();
+        for (oid, val) in table {
+            // column.ifIndex.a.b.c.d
+            let Some(index) = oid_index(&oid, IP_NET_TO_MEDIA_TABLE) else {
+                continue;
+            };
+            if index.len() != 6 {
+                continue;
+            }
+            let if_index = index[1] as i32;
+            let address = IpAddr::V4(Ipv4Addr::new(
+                index[2] as u8,
+                index[3] as u8,
+                index[4] as u8,
+                index[5] as u8,
+            ));
+            let entry = entries
+                .entry((if_index, address))
+                .or_insert_with(|| ArpEntry {
+                    address,
+                    mac: String::new(),
+                    if_index,
+                    entry_type: ArpEntryType::Other,
+                });
+            match index[0] {
+                // ipNetToMediaPhysAddress
+                2 => entry.mac = as_mac(&val).unwrap_or_default(),
+                // ipNetToMediaType
+                4 => entry.entry_type = as_int(&val)?.into(),
+                _ => {}
+            }
+        }
+        Ok(entries.into_values().collect())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_inet_address() {
+        assert_eq!(
+            inet_address(&[1, 4, 192, 168, 88, 1]),
+            Some("192.168.88.1".parse().unwrap())
+        );
+        let mut v6 = vec![2, 16, 0xfe, 0x80];
+        v6.extend([0; 13]);
+        v6.push(1);
+        assert_eq!(inet_address(&v6), Some("fe80::1".parse().unwrap()));
+        assert_eq!(inet_address(&[1, 4, 192, 168]), None);
+    }
+}
@@ -0,0 +1,135 @@
+use crate::query_engine::{as_int, as_ip, as_u32, oid_index, snmp_query};
+use anyhow::Result;
+use serde::Serialize;
+use std::{
+    collections::BTreeMap,
+    net::{IpAddr, Ipv4Addr},
+};
+
+// BGP4-MIB
+const BGP_LOCAL_AS: &str = "1.3.6.1.2.1.15.2";
+const BGP_IDENTIFIER: &str = "1.3.6.1.2.1.15.4";
+const BGP_PEER_TABLE: &str = "1.3.6.1.2.1.15.3.1";
+
+/// bgpPeerState
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize)]
+pub enum BgpPeerState {
+    #[default]
+    Unknown,
+    Idle,
+    Connect,
+    Active,
+    OpenSent,
+    OpenConfirm,
+    Established,
+}
+
+impl From<i32> for BgpPeerState {
+    fn from(value: i32) -> Self {
+        match value {
+            1 => Self::Idle,
+            2 => Self::Connect,
+            3 => Self::Active,
+            4 => Self::OpenSent,
+            5 => Self::OpenConfirm,
+            6 => Self::Established,
+            _ => Self::Unknown,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct BgpPeer {
+    // .7 bgpPeerRemoteAddr (also the table index)
+    pub remote_address: IpAddr,
+    // .1 bgpPeerIdentifier (the peer's router-id)
+    pub remote_router_id: IpAddr,
+    // .2 bgpPeerState
+    pub state: BgpPeerState,
+    // .5 bgpPeerLocalAddr
+    pub local_address: IpAddr,
+    // .9 bgpPeerRemoteAs
+    pub remote_as: u32,
+    // .16 bgpPeerFsmEstablishedTime, in seconds
+    pub established_seconds: u32,
+}
+
+impl BgpPeer {
+    pub fn new(remote_address: IpAddr) -> Self {
+        Self {
+            remote_address,
+            remote_router_id: IpAddr::V4(Ipv4Addr::UNSPECIFIED),
+            state: BgpPeerState::Unknown,
+            local_address: IpAddr::V4(Ipv4Addr::UNSPECIFIED),
+            remote_as: 0,
+            established_seconds: 0,
+        }
+    }
+}
+
+#[derive(Debug, Default, Serialize)]
+pub struct Bgp {
+    pub local_as: Option<u32>,
+    pub router_id: Option<IpAddr>,
+    pub peers: Vec<BgpPeer>,
+}
+
+impl Bgp {
+    /// Is this router running BGP at all?
+    pub fn is_enabled(&self) -> bool {
+        self.local_as.is_some_and(|asn| asn != 0) || !self.peers.is_empty()
+    }
+
+    /// Is the session to `peer` internal (same AS)?
+    pub fn is_ibgp(&self, peer: &BgpPeer) -> bool {
+        self.local_as == Some(peer.remote_as)
+    }
+
+    pub(crate) async fn from_snmp(ip_address: &str, community: &str) -> Result<Self> {
+        // Walk the scalars individually; the rest of BGP4-MIB includes the
+        // path attribute table, which is the size of the full routing table.
+        let mut result = Bgp::default();
+        for (oid, val) in snmp_query(ip_address, community, BGP_LOCAL_AS).await? {
+            if oid == format!("{BGP_LOCAL_AS}.0") {
+                result.local_as = Some(as_u32(&val)?);
+            }
+        }
+        for (oid, val) in snmp_query(ip_address, community, BGP_IDENTIFIER).await? {
+            if oid == format!("{BGP_IDENTIFIER}.0") {
+                // Routers without BGP configured often report 0.0.0.0
+                result.router_id = Some(as_ip(&val)?).filter(|id| !id.is_unspecified());
+            }
+        }
+
+        let mut peers: BTreeMap<IpAddr, BgpPeer> = BTreeMap::new();
+        for (oid, val) in snmp_query(ip_address, community, BGP_PEER_TABLE).await? {
+            // column.a.b.c.d (bgpPeerRemoteAddr)
+            let Some(index) = oid_index(&oid, BGP_PEER_TABLE) else {
+                continue;
+            };
+            if index.len() != 5 {
+                continue;
+            }
+            let remote_address = IpAddr::V4(Ipv4Addr::new(
+                index[1] as u8,
+                index[2] as u8,
+                index[3] as u8,
+                index[4] as u8,
+            ));
+            let peer = peers
+                .entry(remote_address)
+                .or_insert_with(|| BgpPeer::new(remote_address));
+            match index[0] {
+                1 => peer.remote_router_id = as_ip(&val)?,
+                2 => peer.state = as_int(&val)?.into(),
+                5 => peer.local_address = as_ip(&val)?,
+                9 => peer.remote_as = as_u32(&val)?,
+                16 => peer.established_seconds = as_u32(&val)?,
+                _ => {}
+            }
+        }
+        result.peers = peers.into_values().collect();
+
+        Ok(result)
+    }
+}
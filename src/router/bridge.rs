@@ -0,0 +1,260 @@
+use crate::query_engine::{as_bytes, as_int, as_string, format_mac, oid_index, snmp_query};
+use anyhow::Result;
+use serde::Serialize;
+use std::{
+    collections::{BTreeMap, BTreeSet},
+    sync::OnceLock,
+};
+
+// BRIDGE-MIB dot1dBasePortIfIndex
+const BASE_PORT_IF_INDEX: &str = "1.3.6.1.2.1.17.1.4.1.2";
+// BRIDGE-MIB dot1dTpFdbTable (no VLAN awareness)
+const TP_FDB_TABLE: &str = "1.3.6.1.2.1.17.4.3.1";
+// Q-BRIDGE-MIB dot1qTpFdbTable
+const Q_TP_FDB_TABLE: &str = "1.3.6.1.2.1.17.7.1.2.2.1";
+// Q-BRIDGE-MIB dot1qVlanStaticTable
+const Q_VLAN_STATIC_TABLE: &str = "1.3.6.1.2.1.17.7.1.4.3.1";
+
+/// dot1dTpFdbStatus / dot1qTpFdbStatus
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize)]
+pub enum FdbStatus {
+    #[default]
+    Other,
+    Invalid,
+    Learned,
+    /// One of the bridge's own addresses
+    OwnAddress,
+    /// Statically configured
+    Mgmt,
+}
+
+impl From<i32> for FdbStatus {
+    fn from(value: i32) -> Self {
+        match value {
+            2 => Self::Invalid,
+            3 => Self::Learned,
+            4 => Self::OwnAddress,
+            5 => Self::Mgmt,
+            _ => Self::Other,
+        }
+    }
+}
+
+/// A MAC address the bridge has seen, and the port it was seen on.
+#[derive(Debug, Clone, Default, Serialize)]
+pub struct FdbEntry {
+    pub mac: String,
+    /// The filtering database the entry is in (Q-BRIDGE only). On nearly
+    /// every device this is the VLAN ID.
+    pub vlan: Option<u32>,
+    pub port: u32,
+    pub if_index: Option<i32>,
+    pub status: FdbStatus,
+}
+
+/// An 802.1Q VLAN and its member ports.
+#[derive(Debug, Clone, Default, Serialize)]
+pub struct Vlan {
+    pub id: u32,
+    pub name: String,
+    /// Bridge ports carrying the VLAN (tagged or untagged)
+    pub egress_ports: Vec<u32>,
+    /// Bridge ports sending the VLAN untagged
+    pub untagged_ports: Vec<u32>,
+}
+
+/// The bridge's forwarding database and VLANs.
+#[derive(Debug, Default, Serialize)]
+pub struct Bridge {
+    /// Bridge port number -> ifIndex
+    pub ports: BTreeMap<u32, i32>,
+    fdb: Vec<FdbEntry>,
+    pub vlans: Vec<Vlan>,
+    /// Bridge port -> distinct learned MACs, counted on first use
+    #[serde(skip)]
+    port_mac_counts: OnceLock<BTreeMap<u32, usize>>,
+}
+
+/// Decode a PortList bitmap: the most significant bit of the first octet is
+/// port 1.
+fn port_list(bytes: &[u8]) -> Vec<u32> {
+    let mut ports = Vec::new();
+    for (i, byte) in bytes.iter().enumerate() {
+        for bit in 0..8 {
+            if byte & (0x80 >> bit) != 0 {
+                ports.push(i as u32 * 8 + bit + 1);
+            }
+        }
+    }
+    ports
+}
+
+/// The MAC address at the end of an FDB index.
+fn mac_from_index(index: &[u32]) -> Option<String> {
+    if index.len() != 6 {
+        return None;
+    }
+    let bytes: Vec<u8> = index.iter().map(|b| *b as u8).collect();
+    Some(format_mac(&bytes))
+}
+
+impl Bridge {
+    /// The forwarding database.
+    pub fn fdb(&self) -> &[FdbEntry] {
+        &self.fdb
+    }
+
+    /// Replace the forwarding database, dropping the cached port counts.
+    pub(crate) fn set_fdb(&mut self, fdb: Vec<FdbEntry>) {
+        self.fdb = fdb;
+        self.port_mac_counts = OnceLock::new();
+    }
+
+    /// Learned entries for `mac` (in any VLAN).
+    pub fn find_mac<'a>(&'a self, mac: &'a str) -> impl Iterator<Item = &'a FdbEntry> + 'a {
+        self.fdb
+            .iter()
+            .filter(move |e| e.status == FdbStatus::Learned && e.mac.eq_ignore_ascii_case(mac))
+    }
+
+    /// How many distinct MACs have been learned on `port`. Access ports
+    /// see a handful; uplinks see most of the network.
+    pub fn macs_on_port(&self, port: u32) -> usize {
+        let counts = self.port_mac_counts.get_or_init(|| {
+            let mut macs: BTreeMap<u32, BTreeSet<&str>> = BTreeMap::new();
+            for entry in self.fdb.iter().filter(|e| e.status == FdbStatus::Learned) {
+                macs.entry(entry.port).or_default().insert(&entry.mac);
+            }
+            macs.into_iter()
+                .map(|(port, macs)| (port, macs.len()))
+                .collect()
+        });
+        counts.get(&port).copied().unwrap_or_default()
+    }
+
+    pub(crate) async fn from_snmp(ip_address: &str, community: &str) -> Result<Self> {
+        let mut bridge = Bridge::default();
+        for (oid, val) in snmp_query(ip_address, community, BASE_PORT_IF_INDEX).await? {
+            if let Some([port]) = oid_index(&oid, BASE_PORT_IF_INDEX).as_deref() {
+                bridge.ports.insert(*port, as_int(&val)?);
+            }
+        }
+        if bridge.ports.is_empty() {
+            // Not a bridge; plenty of routers aren't
+            return Ok(bridge);
+        }
+
+        let mut fdb = Self::load_q_fdb(ip_address, community).await?;
+        if fdb.is_empty() {
+            fdb = Self::load_fdb(ip_address, community).await?;
+        }
+        for entry in fdb.iter_mut() {
+            entry.if_index = bridge.ports.get(&entry.port).copied();
+        }
+        bridge.set_fdb(fdb);
+
+        // Not every bridge implements Q-BRIDGE-MIB
+        if let Ok(vlans) = Self::load_vlans(ip_address, community).await {
+            bridge.vlans = vlans;
+        }
+        Ok(bridge)
+    }
+
+    /// dot1qTpFdbTable, indexed by fdbId.mac
+    async fn load_q_fdb(ip_address: &str, community: &str) -> Result<Vec<FdbEntry>> {
+        let mut entries: BTreeMap<Vec<u32>, FdbEntry> = BTreeMap::new();
+        for (oid, val) in snmp_query(ip_address, community, Q_TP_FDB_TABLE).await? {
+            let Some(index) = oid_index(&oid, Q_TP_FDB_TABLE) else {
+                continue;
+            };
+            let [column, fdb_id, ref mac @ ..] = index[..] else {
+                continue;
+            };
+            let Some(mac) = mac_from_index(mac) else {
+                continue;
+            };
+            let entry = entries
+                .entry(index[1..].to_vec())
+                .or_insert_with(|| FdbEntry {
+                    mac,
+                    vlan: Some(fdb_id),
+                    ..Default::default()
+                });
+            match column {
+                2 => entry.port = as_int(&val)? as u32,
+                3 => entry.status = as_int(&val)?.into(),
+                _ => {}
+            }
+        }
+        Ok(entries.into_values().collect())
+    }
+
+    /// dot1dTpFdbTable, indexed by mac
+    async fn load_fdb(ip_address: &str, community: &str) -> Result<Vec<FdbEntry>> {
+        let mut entries: BTreeMap<String, FdbEntry> = BTreeMap::new();
+        for (oid, val) in snmp_query(ip_address, community, TP_FDB_TABLE).await? {
+            let Some(index) = oid_index(&oid, TP_FDB_TABLE) else {
+                continue;
+            };
+            let Some((column, mac)) = index.split_first() else {
+                continue;
+            };
+            let Some(mac) = mac_from_index(mac) else {
+                continue;
+            };
+            let entry = entries.entry(mac.clone()).or_insert_with(|| FdbEntry {
+                mac,
+                ..Default::default()
+            });
+            match column {
+                2 => entry.port = as_int(&val)? as u32,
+                3 => entry.status = as_int(&val)?.into(),
+                _ => {}
+            }
+        }
+        Ok(entries.into_values().collect())
+    }
+
+    async fn load_vlans(ip_address: &str, community: &str) -> Result<Vec<Vlan>> {
+        let mut vlans: BTreeMap<u32, Vlan> = BTreeMap::new();
+        for (oid, val) in snmp_query(ip_address, community, Q_VLAN_STATIC_TABLE).await? {
+            let Some(index) = oid_index(&oid, Q_VLAN_STATIC_TABLE) else {
+                continue;
+            };
+            let [column, id] = index[..] else {
+                continue;
+            };
+            let vlan = vlans.entry(id).or_insert_with(|| Vlan {
+                id,
+                ..Default::default()
+            });
+            match column {
+                1 => vlan.name = as_string(&val).unwrap_or_default(),
+                2 => vlan.egress_ports = port_list(as_bytes(&val)?),
+                4 => vlan.untagged_ports = port_list(as_bytes(&val)?),
+                _ => {}
+            }
+        }
+        Ok(vlans.into_values().filter(|v| v.id != 0).collect())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_port_list() {
+        assert_eq!(port_list(&[0b1000_0001, 0b0100_0000]), vec![1, 8, 10]);
+        assert!(port_list(&[0, 0]).is_empty());
+    }
+
+    #[test]
+    fn test_mac_from_index() {
+        assert_eq!(
+            mac_from_index(&[0, 12, 66, 1, 2, 171]).as_deref(),
+            Some("00:0C:42:01:02:AB")
+        );
+        assert_eq!(mac_from_index(&[1, 2, 3]), None);
+    }
+}
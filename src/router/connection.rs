@@ -1,5 +1,14 @@
-#[derive(Debug)]
+use serde::Serialize;
+
+#[derive(Debug, Serialize)]
 pub struct Connection {
     pub snmp_address: String,
+    /// Never exported
+    #[serde(skip_serializing)]
     pub snmp_community: String,
+    /// The VRF whose routing table was used for topology (`None` = global)
+    pub vrf: Option<String>,
+    /// Site tag from the router list, used instead of sysLocation when
+    /// grouping routers by site
+    pub site: Option<String>,
 }
@@ -0,0 +1,349 @@
+use super::Router;
+use crate::query_engine::{as_u32, as_u64, last_index, snmp_query};
+use anyhow::{bail, Result};
+use serde::Serialize;
+use std::{
+    collections::BTreeMap,
+    time::{Duration, Instant},
+};
+use tokio::task::JoinSet;
+use tracing::{debug, warn};
+
+// IF-MIB ifXTable 64-bit octet counters
+const IF_HC_IN_OCTETS: &str = "1.3.6.1.2.1.31.1.1.1.6";
+const IF_HC_OUT_OCTETS: &str = "1.3.6.1.2.1.31.1.1.1.10";
+// IF-MIB ifTable 32-bit counters
+const IF_IN_OCTETS: &str = "1.3.6.1.2.1.2.2.1.10";
+const IF_IN_DISCARDS: &str = "1.3.6.1.2.1.2.2.1.13";
+const IF_IN_ERRORS: &str = "1.3.6.1.2.1.2.2.1.14";
+const IF_OUT_OCTETS: &str = "1.3.6.1.2.1.2.2.1.16";
+const IF_OUT_DISCARDS: &str = "1.3.6.1.2.1.2.2.1.19";
+const IF_OUT_ERRORS: &str = "1.3.6.1.2.1.2.2.1.20";
+
+const COLUMNS: [&str; 8] = [
+    IF_HC_IN_OCTETS,
+    IF_HC_OUT_OCTETS,
+    IF_IN_OCTETS,
+    IF_OUT_OCTETS,
+    IF_IN_ERRORS,
+    IF_OUT_ERRORS,
+    IF_IN_DISCARDS,
+    IF_OUT_DISCARDS,
+];
+
+/// Raw counter values for one interface at one moment. Any column the
+/// device didn't return is `None`.
+#[derive(Debug, Clone, Default)]
+pub struct InterfaceCounters {
+    pub in_octets: Option<u64>,
+    pub out_octets: Option<u64>,
+    /// The in/out octet counters are 64-bit (ifHC*), so a decrease means
+    /// the counter was reset rather than wrapped. Some agents only have one
+    /// of the two HC columns, so each direction is tracked separately.
+    pub in_high_capacity: bool,
+    pub out_high_capacity: bool,
+    pub in_errors: Option<u32>,
+    pub out_errors: Option<u32>,
+    pub in_discards: Option<u32>,
+    pub out_discards: Option<u32>,
+}
+
+/// One walk of a router's interface counters, keyed by ifIndex.
+#[derive(Debug, Clone)]
+pub struct CounterSample {
+    pub taken: Instant,
+    pub interfaces: BTreeMap<i32, InterfaceCounters>,
+}
+
+/// Traffic on one interface, averaged between two samples.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Serialize)]
+pub struct InterfaceRates {
+    pub in_bps: f64,
+    pub out_bps: f64,
+    pub in_errors_per_sec: f64,
+    pub out_errors_per_sec: f64,
+    pub in_discards_per_sec: f64,
+    pub out_discards_per_sec: f64,
+}
+
+impl InterfaceRates {
+    /// The busier direction as a fraction (0.0 - 1.0) of `speed_mbps`.
+    pub fn utilisation(&self, speed_mbps: u64) -> Option<f64> {
+        if speed_mbps == 0 {
+            return None;
+        }
+        Some(self.in_bps.max(self.out_bps) / (speed_mbps as f64 * 1_000_000.0))
+    }
+
+    /// The same traffic, seen from the other end of the link.
+    pub fn reversed(&self) -> Self {
+        Self {
+            in_bps: self.out_bps,
+            out_bps: self.in_bps,
+            in_errors_per_sec: self.out_errors_per_sec,
+            out_errors_per_sec: self.in_errors_per_sec,
+            in_discards_per_sec: self.out_discards_per_sec,
+            out_discards_per_sec: self.in_discards_per_sec,
+        }
+    }
+}
+
+/// Measured traffic for a router's interfaces, keyed by ifIndex.
+#[derive(Debug, Default, Serialize)]
+pub struct Traffic {
+    pub rates: BTreeMap<i32, InterfaceRates>,
+}
+
+impl Traffic {
+    pub fn get(&self, if_index: i32) -> Option<&InterfaceRates> {
+        self.rates.get(&if_index)
+    }
+}
+
+/// How far a counter moved between two readings. 32-bit counters wrap;
+/// 64-bit ones effectively never do, so going backwards means the device
+/// restarted (or the counter was cleared) and there is no usable delta.
+fn counter_delta(before: u64, after: u64, bits: u32) -> Option<u64> {
+    if after >= before {
+        Some(after - before)
+    } else if bits == 32 && before <= u32::MAX as u64 {
+        Some(after + (1u64 << 32) - before)
+    } else {
+        None
+    }
+}
+
+impl CounterSample {
+    pub(crate) async fn from_snmp(ip_address: &str, community: &str) -> Result<Self> {
+        // Walk every column at once, and stamp the sample as the walks start
+        // so that both samples are timed the same way
+        let taken = Instant::now();
+        let mut set = JoinSet::new();
+        for column in COLUMNS {
+            let ip = ip_address.to_string();
+            let community = community.to_string();
+            set.spawn(async move { (column, snmp_query(&ip, &community, column).await) });
+        }
+        let mut walks = BTreeMap::new();
+        while let Some(res) = set.join_next().await {
+            match res {
+                Ok((column, Ok(values))) => {
+                    walks.insert(column, values);
+                }
+                // Not every device has every column
+                Ok((column, Err(e))) => debug!("{ip_address}: unable to walk {column}: {e}"),
+                Err(e) => warn!("JoinSet Error: {:?}", e),
+            }
+        }
+
+        let mut interfaces: BTreeMap<i32, InterfaceCounters> = BTreeMap::new();
+        // Prefer the 64-bit counters: a 1 Gbps link wraps ifInOctets in 34 seconds
+        for column in COLUMNS {
+            for (oid, val) in walks.remove(column).unwrap_or_default() {
+                let counters = interfaces.entry(last_index(&oid)?).or_default();
+                match column {
+                    IF_HC_IN_OCTETS => {
+                        if let Ok(value) = as_u64(&val) {
+                            counters.in_octets = Some(value);
+                            counters.in_high_capacity = true;
+                        }
+                    }
+                    IF_HC_OUT_OCTETS => {
+                        if let Ok(value) = as_u64(&val) {
+                            counters.out_octets = Some(value);
+                            counters.out_high_capacity = true;
+                        }
+                    }
+                    IF_IN_OCTETS if !counters.in_high_capacity => {
+                        counters.in_octets = as_u32(&val).ok().map(u64::from)
+                    }
+                    IF_OUT_OCTETS if !counters.out_high_capacity => {
+                        counters.out_octets = as_u32(&val).ok().map(u64::from)
+                    }
+                    IF_IN_ERRORS => counters.in_errors = as_u32(&val).ok(),
+                    IF_OUT_ERRORS => counters.out_errors = as_u32(&val).ok(),
+                    IF_IN_DISCARDS => counters.in_discards = as_u32(&val).ok(),
+                    IF_OUT_DISCARDS => counters.out_discards = as_u32(&val).ok(),
+                    _ => {}
+                }
+            }
+        }
+
+        if interfaces.is_empty() {
+            bail!("No interface counters");
+        }
+        Ok(Self { taken, interfaces })
+    }
+
+    /// Per-interface rates between this sample and a `later` one.
+    /// Interfaces whose counters were reset in between, or that are missing
+    /// an octet counter, are left out; a missing error or discard counter
+    /// gives a rate of zero.
+    pub fn rates(&self, later: &CounterSample) -> BTreeMap<i32, InterfaceRates> {
+        let seconds = later.taken.duration_since(self.taken).as_secs_f64();
+        let mut result = BTreeMap::new();
+        if seconds <= 0.0 {
+            return result;
+        }
+        for (index, before) in self.interfaces.iter() {
+            let Some(after) = later.interfaces.get(index) else {
+                continue;
+            };
+            let bits = |b: bool, a: bool| if b && a { 64 } else { 32 };
+            let octets = |b: Option<u64>, a: Option<u64>, bits: u32| counter_delta(b?, a?, bits);
+            // Outer None for a reset, inner None for a missing column
+            let count = |b: Option<u32>, a: Option<u32>| match (b, a) {
+                (Some(b), Some(a)) => counter_delta(b as u64, a as u64, 32).map(Some),
+                _ => Some(None),
+            };
+            let (
+                Some(in_octets),
+                Some(out_octets),
+                Some(in_errors),
+                Some(out_errors),
+                Some(in_discards),
+                Some(out_discards),
+            ) = (
+                octets(
+                    before.in_octets,
+                    after.in_octets,
+                    bits(before.in_high_capacity, after.in_high_capacity),
+                ),
+                octets(
+                    before.out_octets,
+                    after.out_octets,
+                    bits(before.out_high_capacity, after.out_high_capacity),
+                ),
+                count(before.in_errors, after.in_errors),
+                count(before.out_errors, after.out_errors),
+                count(before.in_discards, after.in_discards),
+                count(before.out_discards, after.out_discards),
+            )
+            else {
+                continue;
+            };
+            let per_sec = |count: Option<u64>| count.unwrap_or_default() as f64 / seconds;
+            result.insert(
+                *index,
+                InterfaceRates {
+                    in_bps: in_octets as f64 * 8.0 / seconds,
+                    out_bps: out_octets as f64 * 8.0 / seconds,
+                    in_errors_per_sec: per_sec(in_errors),
+                    out_errors_per_sec: per_sec(out_errors),
+                    in_discards_per_sec: per_sec(in_discards),
+                    out_discards_per_sec: per_sec(out_discards),
+                },
+            );
+        }
+        result
+    }
+}
+
+/// Sample every router's counters concurrently. Routers that fail to
+/// answer get `None`.
+async fn sample_all(routers: &[Router]) -> Vec<Option<CounterSample>> {
+    let mut set = JoinSet::new();
+    for (i, router) in routers.iter().enumerate() {
+        let ip = router.connection.snmp_address.clone();
+        let community = router.connection.snmp_community.clone();
+        set.spawn(async move {
+            (
+                i,
+                ip.clone(),
+                CounterSample::from_snmp(&ip, &community).await,
+            )
+        });
+    }
+
+    let mut samples = vec![None; routers.len()];
+    while let Some(res) = set.join_next().await {
+        match res {
+            Ok((i, _, Ok(sample))) => samples[i] = Some(sample),
+            Ok((_, ip, Err(e))) => warn!("Unable to read interface counters from {ip}: {e}"),
+            Err(e) => warn!("JoinSet Error: {:?}", e),
+        }
+    }
+    samples
+}
+
+/// Sample every router's interface counters twice, `interval` apart, and
+/// store the resulting rates on each router.
+pub async fn poll_traffic(routers: &mut [Router], interval: Duration) {
+    let first = sample_all(routers).await;
+    tokio::time::sleep(interval).await;
+    let second = sample_all(routers).await;
+
+    for ((router, first), second) in routers.iter_mut().zip(first).zip(second) {
+        if let (Some(first), Some(second)) = (first, second) {
+            router.traffic.rates = first.rates(&second);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_counter_wrap() {
+        assert_eq!(counter_delta(100, 250, 32), Some(150));
+        assert_eq!(counter_delta(u32::MAX as u64 - 9, 10, 32), Some(20));
+        // A 64-bit counter going backwards is a reset, not a wrap
+        assert_eq!(counter_delta(1 << 40, 10, 64), None);
+    }
+
+    #[test]
+    fn test_rates() {
+        let start = Instant::now();
+        let counters = |octets: u64, errors: u32| InterfaceCounters {
+            in_octets: Some(octets),
+            out_octets: Some(octets / 2),
+            in_high_capacity: true,
+            out_high_capacity: true,
+            in_errors: Some(errors),
+            ..Default::default()
+        };
+        let before = CounterSample {
+            taken: start,
+            interfaces: BTreeMap::from([(1, counters(1_000, 0)), (2, counters(5_000, 0))]),
+        };
+        let after = CounterSample {
+            taken: start + Duration::from_secs(10),
+            // Interface 2 was reset
+            interfaces: BTreeMap::from([(1, counters(1_251_000, 20)), (2, counters(10, 0))]),
+        };
+        let rates = before.rates(&after);
+        assert_eq!(rates.len(), 1);
+        // No discard counters on this device
+        assert_eq!(rates[&1].in_discards_per_sec, 0.0);
+        let rates = rates[&1];
+        assert_eq!(rates.in_bps, 1_000_000.0);
+        assert_eq!(rates.out_bps, 500_000.0);
+        assert_eq!(rates.in_errors_per_sec, 2.0);
+        assert_eq!(rates.utilisation(10), Some(0.1));
+    }
+
+    #[test]
+    fn test_mixed_counter_widths() {
+        let start = Instant::now();
+        // ifHCInOctets but only the 32-bit ifOutOctets
+        let counters = |in_octets: u64, out_octets: u64| InterfaceCounters {
+            in_octets: Some(in_octets),
+            out_octets: Some(out_octets),
+            in_high_capacity: true,
+            ..Default::default()
+        };
+        let before = CounterSample {
+            taken: start,
+            interfaces: BTreeMap::from([(1, counters(1 << 40, u32::MAX as u64 - 9))]),
+        };
+        let after = CounterSample {
+            taken: start + Duration::from_secs(10),
+            interfaces: BTreeMap::from([(1, counters((1 << 40) + 1_000, 90))]),
+        };
+        let rates = before.rates(&after);
+        // The outbound counter wrapped rather than reset
+        assert_eq!(rates[&1].in_bps, 800.0);
+        assert_eq!(rates[&1].out_bps, 80.0);
+    }
+}
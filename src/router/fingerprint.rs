@@ -0,0 +1,277 @@
+use super::{inventory::Inventory, system_info::SystemInfo};
+use once_cell::sync::Lazy;
+use regex::Regex;
+use serde::{Deserialize, Serialize};
+
+/// Who made the device.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Deserialize, Serialize)]
+pub enum Vendor {
+    MikroTik,
+    Cisco,
+    Juniper,
+    Ubiquiti,
+    Cambium,
+    Huawei,
+    Arista,
+    /// Linux (or BSD) running net-snmp
+    NetSnmp,
+    #[default]
+    Unknown,
+}
+
+impl Vendor {
+    pub fn name(&self) -> &'static str {
+        match self {
+            Vendor::MikroTik => "MikroTik",
+            Vendor::Cisco => "Cisco",
+            Vendor::Juniper => "Juniper",
+            Vendor::Ubiquiti => "Ubiquiti",
+            Vendor::Cambium => "Cambium",
+            Vendor::Huawei => "Huawei",
+            Vendor::Arista => "Arista",
+            Vendor::NetSnmp => "net-snmp",
+            Vendor::Unknown => "Unknown",
+        }
+    }
+}
+
+/// IANA enterprise number -> vendor
+const ENTERPRISES: &[(u32, Vendor)] = &[
+    (9, Vendor::Cisco),
+    (161, Vendor::Cambium), // Motorola Canopy, now Cambium PMP
+    (2011, Vendor::Huawei),
+    (2636, Vendor::Juniper),
+    (4413, Vendor::Ubiquiti), // EdgeOS on older firmware (Broadcom)
+    (8072, Vendor::NetSnmp),
+    (10002, Vendor::Ubiquiti), // airOS reports Frogfoot's enterprise number
+    (14988, Vendor::MikroTik),
+    (17713, Vendor::Cambium),
+    (30065, Vendor::Arista),
+    (41112, Vendor::Ubiquiti),
+];
+
+/// A sysDescr pattern: the vendor it identifies (if sysObjectID didn't),
+/// and regexes whose first capture group is the model / OS version.
+struct DescrPattern {
+    matches: Regex,
+    vendor: Vendor,
+    os: &'static str,
+    model: Option<Regex>,
+    version: Option<Regex>,
+}
+
+fn re(pattern: &str) -> Regex {
+    Regex::new(pattern).unwrap()
+}
+
+static DESCR_PATTERNS: Lazy<Vec<DescrPattern>> = Lazy::new(|| {
+    vec![
+        // "RouterOS RB4011iGS+"
+        DescrPattern {
+            matches: re(r"^RouterOS"),
+            vendor: Vendor::MikroTik,
+            os: "RouterOS",
+            model: Some(re(r"^RouterOS (\S+)")),
+            version: None,
+        },
+        // "Cisco IOS XE Software, Version 16.09.04"
+        // "Cisco IOS Software [Fuji], ASR1000 Software (X86_64_LINUX_IOSD-UNIVERSALK9-M), Version 16.9.4, ..."
+        // The bracketed part is the image, not the model; when sysDescr
+        // doesn't name a platform the model comes from ENTITY-MIB instead
+        DescrPattern {
+            matches: re(r"Cisco IOS[ -]XE|_IOSD-|_IOSXE"),
+            vendor: Vendor::Cisco,
+            os: "IOS XE",
+            model: Some(re(r", ([^,]+?) Software \(")),
+            version: Some(re(r"Version ([^,\s]+)")),
+        },
+        // "Cisco IOS Software, C2900 Software (C2900-UNIVERSALK9-M), Version 15.2(4)M7, ..."
+        DescrPattern {
+            matches: re(r"Cisco (IOS|Internetwork Operating System)"),
+            vendor: Vendor::Cisco,
+            os: "IOS",
+            model: Some(re(r", (\S+) Software \(")),
+            version: Some(re(r"Version ([^,\s]+)")),
+        },
+        DescrPattern {
+            matches: re(r"Cisco NX-OS"),
+            vendor: Vendor::Cisco,
+            os: "NX-OS",
+            model: None,
+            version: Some(re(r"Version ([^,\s]+)")),
+        },
+        // "Juniper Networks, Inc. mx480 internet router, kernel JUNOS 18.4R3-S4.2, ..."
+        DescrPattern {
+            matches: re(r"Juniper Networks"),
+            vendor: Vendor::Juniper,
+            os: "Junos",
+            model: Some(re(r"Inc\. (\S+)")),
+            version: Some(re(r"JUNOS ([^,\s]+)")),
+        },
+        // "EdgeOS v2.0.9-hotfix.2.5402463.210511.1317"
+        DescrPattern {
+            matches: re(r"EdgeOS"),
+            vendor: Vendor::Ubiquiti,
+            os: "EdgeOS",
+            model: None,
+            version: Some(re(r"EdgeOS v(\S+)")),
+        },
+        // "Linux gw1 5.10.0-21-amd64 #1 SMP Debian 5.10.162-1 (2023-01-21) x86_64"
+        DescrPattern {
+            matches: re(r"^Linux "),
+            vendor: Vendor::NetSnmp,
+            os: "Linux",
+            model: Some(re(r" (\S+)$")),
+            version: Some(re(r"^Linux \S+ (\S+)")),
+        },
+        // "FreeBSD fw1 13.2-RELEASE FreeBSD 13.2-RELEASE ..."
+        DescrPattern {
+            matches: re(r"^FreeBSD "),
+            vendor: Vendor::NetSnmp,
+            os: "FreeBSD",
+            model: None,
+            version: Some(re(r"^FreeBSD \S+ (\S+)")),
+        },
+    ]
+});
+
+fn capture(regex: &Option<Regex>, text: &str) -> Option<String> {
+    Some(regex.as_ref()?.captures(text)?.get(1)?.as_str().to_string())
+}
+
+/// What the device is, worked out from sysObjectID, sysDescr and (if
+/// available) ENTITY-MIB.
+#[derive(Debug, Clone, Default, Serialize)]
+pub struct Fingerprint {
+    pub vendor: Vendor,
+    pub model: String,
+    pub os: String,
+    pub os_version: String,
+}
+
+impl Fingerprint {
+    /// Identify a device from its system group alone.
+    pub fn identify(system_info: &SystemInfo) -> Self {
+        let mut result = Fingerprint {
+            vendor: system_info
+                .enterprise_number()
+                .and_then(|n| ENTERPRISES.iter().find(|(e, _)| *e == n))
+                .map(|(_, vendor)| *vendor)
+                .unwrap_or_default(),
+            ..Default::default()
+        };
+
+        let descr = system_info.platform.trim();
+        if let Some(pattern) = DESCR_PATTERNS.iter().find(|p| {
+            p.matches.is_match(descr)
+                && (result.vendor == Vendor::Unknown || result.vendor == p.vendor)
+        }) {
+            result.vendor = pattern.vendor;
+            result.os = pattern.os.to_string();
+            result.model = capture(&pattern.model, descr).unwrap_or_default();
+            result.os_version = capture(&pattern.version, descr).unwrap_or_default();
+        }
+        result
+    }
+
+    /// e.g. "MikroTik CCR1036-8G-2S+ RouterOS 6.49.7", leaving out anything
+    /// that wasn't identified.
+    pub fn platform(&self) -> String {
+        let vendor = match self.vendor {
+            Vendor::Unknown => "",
+            vendor => vendor.name(),
+        };
+        [vendor, &self.model, &self.os, &self.os_version]
+            .iter()
+            .filter(|s| !s.is_empty())
+            .copied()
+            .collect::<Vec<_>>()
+            .join(" ")
+    }
+
+    /// Fill in anything sysDescr didn't tell us from the ENTITY-MIB chassis.
+    pub fn refine(&mut self, inventory: &Inventory) {
+        let Some(chassis) = inventory.chassis() else {
+            return;
+        };
+        if !chassis.model_name.is_empty() {
+            self.model = chassis.model_name.clone();
+        }
+        if self.os_version.is_empty() && !chassis.software_revision.is_empty() {
+            self.os_version = chassis.software_revision.clone();
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn identify(object_id: &str, descr: &str) -> Fingerprint {
+        Fingerprint::identify(&SystemInfo {
+            object_id: object_id.to_string(),
+            platform: descr.to_string(),
+            ..Default::default()
+        })
+    }
+
+    #[test]
+    fn test_mikrotik() {
+        let fp = identify("1.3.6.1.4.1.14988.1", "RouterOS RB4011iGS+");
+        assert_eq!(fp.vendor, Vendor::MikroTik);
+        assert_eq!(fp.model, "RB4011iGS+");
+    }
+
+    #[test]
+    fn test_cisco_ios() {
+        let fp = identify(
+            "1.3.6.1.4.1.9.1.1041",
+            "Cisco IOS Software, C2900 Software (C2900-UNIVERSALK9-M), Version 15.2(4)M7, RELEASE SOFTWARE (fc2)",
+        );
+        assert_eq!(fp.vendor, Vendor::Cisco);
+        assert_eq!(fp.os, "IOS");
+        assert_eq!(fp.model, "C2900");
+        assert_eq!(fp.os_version, "15.2(4)M7");
+    }
+
+    #[test]
+    fn test_cisco_ios_xe() {
+        let fp = identify(
+            "1.3.6.1.4.1.9.1.1165",
+            "Cisco IOS XE Software, Version 16.09.04",
+        );
+        assert_eq!(fp.os, "IOS XE");
+        assert_eq!(fp.model, "");
+        assert_eq!(fp.os_version, "16.09.04");
+
+        let fp = identify(
+            "1.3.6.1.4.1.9.1.1165",
+            "Cisco IOS Software [Fuji], ASR1000 Software (X86_64_LINUX_IOSD-UNIVERSALK9-M), Version 16.9.4, RELEASE SOFTWARE (fc2)",
+        );
+        assert_eq!(fp.os, "IOS XE");
+        assert_eq!(fp.model, "ASR1000");
+        assert_eq!(fp.os_version, "16.9.4");
+    }
+
+    #[test]
+    fn test_juniper() {
+        let fp = identify(
+            "1.3.6.1.4.1.2636.1.1.1.2.25",
+            "Juniper Networks, Inc. mx480 internet router, kernel JUNOS 18.4R3-S4.2, Build date: 2020-06-03",
+        );
+        assert_eq!(fp.vendor, Vendor::Juniper);
+        assert_eq!(fp.model, "mx480");
+        assert_eq!(fp.os_version, "18.4R3-S4.2");
+    }
+
+    #[test]
+    fn test_linux_by_descr_only() {
+        let fp = identify(
+            "",
+            "Linux gw1 5.10.0-21-amd64 #1 SMP Debian 5.10.162-1 (2023-01-21) x86_64",
+        );
+        assert_eq!(fp.vendor, Vendor::NetSnmp);
+        assert_eq!(fp.os_version, "5.10.0-21-amd64");
+        assert_eq!(fp.model, "x86_64");
+    }
+}
@@ -0,0 +1,90 @@
+//! Hand-built routers for unit tests.
+use super::{
+    arp_table::{ArpEntry, ArpEntryType, ArpTable},
+    bgp::Bgp,
+    bridge::{FdbEntry, FdbStatus},
+    connection::Connection,
+    interfaces::Interfaces,
+    inventory::Inventory,
+    ip_routes::{CidrEntry, IpRoutes},
+    ip_table::{IpAddress, IpTable},
+    neighbors::Neighbors,
+    ospf::Ospf,
+    system_info::SystemInfo,
+    Router,
+};
+
+pub(crate) fn route(destination: &str, netmask: u8, next_hop: &str) -> CidrEntry {
+    CidrEntry {
+        destination: destination.parse().unwrap(),
+        netmask,
+        next_hop: next_hop.parse().unwrap(),
+        if_index: 1,
+    }
+}
+
+/// A router called `hostname` owning each of `addresses` (as "ip/prefix").
+pub(crate) fn router(hostname: &str, addresses: &[&str], routes: Vec<CidrEntry>) -> Router {
+    let ips = addresses
+        .iter()
+        .enumerate()
+        .map(|(idx, address)| {
+            let (ip, prefix) = address.split_once('/').unwrap();
+            IpAddress {
+                address: ip.parse().unwrap(),
+                interface_index: idx as i32 + 1,
+                cidr_mask: prefix.parse().unwrap(),
+            }
+        })
+        .collect::<Vec<_>>();
+    Router {
+        connection: Connection {
+            snmp_address: ips[0].address.to_string(),
+            snmp_community: "public".to_string(),
+            vrf: None,
+            site: None,
+        },
+        system_info: SystemInfo {
+            hostname: hostname.to_string(),
+            ..Default::default()
+        },
+        ip_table: IpTable { ips },
+        ip_routes: IpRoutes { routes },
+        interfaces: Interfaces::default(),
+        neighbors: Neighbors::default(),
+        arp_table: ArpTable::default(),
+        bgp: Bgp::default(),
+        ospf: Ospf::default(),
+        inventory: Inventory::default(),
+        bridge: Default::default(),
+        fingerprint: Default::default(),
+        health: Default::default(),
+        vrfs: Default::default(),
+        wireless: Default::default(),
+        profile: String::new(),
+        extra_tables: Default::default(),
+        traffic: Default::default(),
+    }
+}
+
+/// A learned forwarding database entry on bridge port `port` (ifIndex
+/// `port + 100`).
+pub(crate) fn learned(mac: &str, port: u32) -> FdbEntry {
+    FdbEntry {
+        mac: mac.to_string(),
+        port,
+        if_index: Some(port as i32 + 100),
+        status: FdbStatus::Learned,
+        ..Default::default()
+    }
+}
+
+/// A dynamic ARP entry mapping `address` to `mac` on ifIndex 1.
+pub(crate) fn arp(address: &str, mac: &str) -> ArpEntry {
+    ArpEntry {
+        address: address.parse().unwrap(),
+        mac: mac.to_string(),
+        if_index: 1,
+        entry_type: ArpEntryType::Dynamic,
+    }
+}
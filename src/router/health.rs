@@ -0,0 +1,299 @@
+use super::Vendor;
+use crate::csnmp::ObjectValue;
+use crate::query_engine::{as_int, as_oid, as_string, as_u32, last_index, oid_index, snmp_query};
+use anyhow::{anyhow, bail, Result};
+use serde::Serialize;
+use std::collections::BTreeMap;
+
+// HOST-RESOURCES-MIB
+const HR_PROCESSOR_LOAD: &str = "1.3.6.1.2.1.25.3.3.1.2";
+const HR_STORAGE_TABLE: &str = "1.3.6.1.2.1.25.2.3.1";
+const HR_STORAGE_RAM: &str = "1.3.6.1.2.1.25.2.1.2";
+const HR_STORAGE_FIXED_DISK: &str = "1.3.6.1.2.1.25.2.1.4";
+const HR_STORAGE_FLASH: &str = "1.3.6.1.2.1.25.2.1.9";
+// MIKROTIK-MIB mtxrHealth scalars, values in tenths of a degree. A walk
+// only returns what is below its root, so walk these and read `.0`
+const MTXR_TEMPERATURE: &str = "1.3.6.1.4.1.14988.1.1.3.10";
+const MTXR_PROCESSOR_TEMPERATURE: &str = "1.3.6.1.4.1.14988.1.1.3.11";
+// CISCO-PROCESS-MIB cpmCPUTotal5minRev
+const CPM_CPU_TOTAL_5MIN: &str = "1.3.6.1.4.1.9.9.109.1.1.1.1.8";
+// CISCO-ENVMON-MIB ciscoEnvMonTemperatureStatusTable
+const ENV_MON_TEMPERATURE_TABLE: &str = "1.3.6.1.4.1.9.9.13.1.3.1";
+// CISCO-MEMORY-POOL-MIB ciscoMemoryPoolTable
+const MEMORY_POOL_TABLE: &str = "1.3.6.1.4.1.9.9.48.1.1.1";
+
+// Above these a router is reported as overloaded
+const CPU_WARNING_PERCENT: f64 = 90.0;
+const MEMORY_WARNING_PERCENT: f64 = 90.0;
+const STORAGE_WARNING_PERCENT: f64 = 95.0;
+const TEMPERATURE_WARNING_CELSIUS: f64 = 75.0;
+
+/// How full a memory pool or disk is.
+#[derive(Debug, Clone, Default, PartialEq, Serialize)]
+pub struct StorageUsage {
+    pub name: String,
+    pub total_bytes: u64,
+    pub used_bytes: u64,
+}
+
+impl StorageUsage {
+    /// Build from an hrStorageEntry. The columns are Integer32, and some
+    /// agents report large disks as negative numbers; those rows are
+    /// skipped rather than guessed at.
+    fn from_hr_storage(name: String, units: i32, size: i32, used: i32) -> Option<Self> {
+        let units = u64::try_from(units).ok()?;
+        Some(Self {
+            name,
+            total_bytes: u64::try_from(size).ok()?.checked_mul(units)?,
+            used_bytes: u64::try_from(used).ok()?.checked_mul(units)?,
+        })
+    }
+
+    pub fn used_percent(&self) -> Option<f64> {
+        if self.total_bytes == 0 {
+            return None;
+        }
+        Some(self.used_bytes as f64 * 100.0 / self.total_bytes as f64)
+    }
+}
+
+#[derive(Debug, Clone, Default, PartialEq, Serialize)]
+pub struct Temperature {
+    pub name: String,
+    pub celsius: f64,
+}
+
+/// CPU, memory, disk and temperature readings, normalised across
+/// HOST-RESOURCES-MIB and vendor MIBs.
+#[derive(Debug, Clone, Default, Serialize)]
+pub struct Health {
+    /// Load of each processor, in percent
+    pub cpu_load: Vec<f64>,
+    pub memory: Option<StorageUsage>,
+    pub disks: Vec<StorageUsage>,
+    pub temperatures: Vec<Temperature>,
+}
+
+impl Health {
+    /// Average load across all processors.
+    pub fn cpu_percent(&self) -> Option<f64> {
+        if self.cpu_load.is_empty() {
+            return None;
+        }
+        Some(self.cpu_load.iter().sum::<f64>() / self.cpu_load.len() as f64)
+    }
+
+    pub fn memory_percent(&self) -> Option<f64> {
+        self.memory.as_ref()?.used_percent()
+    }
+
+    pub fn max_temperature(&self) -> Option<f64> {
+        self.temperatures
+            .iter()
+            .map(|t| t.celsius)
+            .max_by(|a, b| a.total_cmp(b))
+    }
+
+    /// Human-readable descriptions of every reading over its threshold.
+    pub fn problems(&self) -> Vec<String> {
+        let mut problems = Vec::new();
+        if let Some(cpu) = self.cpu_percent().filter(|c| *c >= CPU_WARNING_PERCENT) {
+            problems.push(format!("CPU at {cpu:.0}%"));
+        }
+        if let Some(memory) = self
+            .memory_percent()
+            .filter(|m| *m >= MEMORY_WARNING_PERCENT)
+        {
+            problems.push(format!("memory {memory:.0}% used"));
+        }
+        for disk in self.disks.iter() {
+            if let Some(used) = disk
+                .used_percent()
+                .filter(|u| *u >= STORAGE_WARNING_PERCENT)
+            {
+                problems.push(format!("{} {used:.0}% full", disk.name));
+            }
+        }
+        for temperature in self.temperatures.iter() {
+            if temperature.celsius >= TEMPERATURE_WARNING_CELSIUS {
+                problems.push(format!(
+                    "{} at {:.1}°C",
+                    temperature.name, temperature.celsius
+                ));
+            }
+        }
+        problems
+    }
+
+    pub(crate) async fn from_snmp(
+        ip_address: &str,
+        community: &str,
+        vendor: Vendor,
+    ) -> Result<Self> {
+        let mut health = Health::default();
+        let host_resources = health.load_host_resources(ip_address, community).await;
+        let vendor_health = match vendor {
+            Vendor::MikroTik => health.load_mikrotik(ip_address, community).await,
+            Vendor::Cisco => health.load_cisco(ip_address, community).await,
+            _ => Err(anyhow!("no vendor health MIB")),
+        };
+        if let (Err(e), Err(_)) = (&host_resources, &vendor_health) {
+            bail!("No health information: {e}");
+        }
+        Ok(health)
+    }
+
+    async fn load_host_resources(&mut self, ip_address: &str, community: &str) -> Result<()> {
+        for (_, val) in snmp_query(ip_address, community, HR_PROCESSOR_LOAD).await? {
+            self.cpu_load.push(as_int(&val)? as f64);
+        }
+
+        // hrStorageType, hrStorageDescr, hrStorageAllocationUnits, hrStorageSize, hrStorageUsed
+        let mut rows: BTreeMap<u32, (String, String, i32, i32, i32)> = BTreeMap::new();
+        for (oid, val) in snmp_query(ip_address, community, HR_STORAGE_TABLE).await? {
+            let Some(index) = oid_index(&oid, HR_STORAGE_TABLE) else {
+                continue;
+            };
+            let [column, row] = index[..] else {
+                continue;
+            };
+            let entry = rows.entry(row).or_default();
+            match column {
+                2 => entry.0 = as_oid(&val)?,
+                3 => entry.1 = as_string(&val).unwrap_or_default(),
+                4 => entry.2 = as_int(&val)?,
+                5 => entry.3 = as_int(&val)?,
+                6 => entry.4 = as_int(&val)?,
+                _ => {}
+            }
+        }
+        for (storage_type, name, units, size, used) in rows.into_values() {
+            let Some(usage) = StorageUsage::from_hr_storage(name, units, size, used) else {
+                continue;
+            };
+            match storage_type.as_str() {
+                HR_STORAGE_RAM if self.memory.is_none() => self.memory = Some(usage),
+                HR_STORAGE_FIXED_DISK | HR_STORAGE_FLASH => self.disks.push(usage),
+                _ => {}
+            }
+        }
+        // An agent without HOST-RESOURCES-MIB answers the walks with nothing
+        if self.cpu_load.is_empty() && self.memory.is_none() && self.disks.is_empty() {
+            bail!("HOST-RESOURCES-MIB not available");
+        }
+        Ok(())
+    }
+
+    async fn load_mikrotik(&mut self, ip_address: &str, community: &str) -> Result<()> {
+        for (oid, name) in [
+            (MTXR_TEMPERATURE, "Board"),
+            (MTXR_PROCESSOR_TEMPERATURE, "Processor"),
+        ] {
+            let walk = snmp_query(ip_address, community, oid).await?;
+            if let Some(celsius) = mikrotik_temperature(&walk, oid) {
+                self.temperatures.push(Temperature {
+                    name: name.to_string(),
+                    celsius,
+                });
+            }
+        }
+        Ok(())
+    }
+
+    async fn load_cisco(&mut self, ip_address: &str, community: &str) -> Result<()> {
+        // IOS doesn't implement HOST-RESOURCES-MIB; prefer its own CPU figures
+        let cpu = snmp_query(ip_address, community, CPM_CPU_TOTAL_5MIN).await?;
+        if !cpu.is_empty() {
+            self.cpu_load = cpu
+                .iter()
+                .map(|(_, val)| as_u32(val).map(|v| v as f64))
+                .collect::<Result<_>>()?;
+        }
+
+        let mut names: BTreeMap<i32, String> = BTreeMap::new();
+        for (oid, val) in snmp_query(ip_address, community, ENV_MON_TEMPERATURE_TABLE).await? {
+            let index = last_index(&oid)?;
+            if oid.starts_with("1.3.6.1.4.1.9.9.13.1.3.1.2.") {
+                names.insert(index, as_string(&val).unwrap_or_default());
+            } else if oid.starts_with("1.3.6.1.4.1.9.9.13.1.3.1.3.") {
+                self.temperatures.push(Temperature {
+                    name: names.get(&index).cloned().unwrap_or_default(),
+                    celsius: as_u32(&val)? as f64,
+                });
+            }
+        }
+
+        // ciscoMemoryPoolName .2, Used .5, Free .6; pool 1 is the processor pool
+        let mut pool = StorageUsage::default();
+        let mut free = 0;
+        for (oid, val) in snmp_query(ip_address, community, MEMORY_POOL_TABLE).await? {
+            match oid_index(&oid, MEMORY_POOL_TABLE).as_deref() {
+                Some([2, 1]) => pool.name = as_string(&val).unwrap_or_default(),
+                Some([5, 1]) => pool.used_bytes = as_u32(&val)? as u64,
+                Some([6, 1]) => free = as_u32(&val)? as u64,
+                _ => {}
+            }
+        }
+        if pool.used_bytes + free > 0 && self.memory.is_none() {
+            pool.total_bytes = pool.used_bytes + free;
+            self.memory = Some(pool);
+        }
+        Ok(())
+    }
+}
+
+/// Read a MIKROTIK-MIB temperature scalar from a walk of `oid`.
+fn mikrotik_temperature(walk: &[(String, ObjectValue)], oid: &str) -> Option<f64> {
+    let instance = format!("{oid}.0");
+    let (_, val) = walk.iter().find(|(o, _)| *o == instance)?;
+    Some(as_int(val).ok()? as f64 / 10.0)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_problems() {
+        let health = Health {
+            cpu_load: vec![95.0, 99.0],
+            memory: Some(StorageUsage {
+                name: "Physical memory".to_string(),
+                total_bytes: 1000,
+                used_bytes: 500,
+            }),
+            disks: vec![],
+            temperatures: vec![Temperature {
+                name: "Board".to_string(),
+                celsius: 81.5,
+            }],
+        };
+        assert_eq!(health.cpu_percent(), Some(97.0));
+        assert_eq!(health.memory_percent(), Some(50.0));
+        assert_eq!(health.problems(), vec!["CPU at 97%", "Board at 81.5°C"]);
+    }
+
+    #[test]
+    fn test_hr_storage() {
+        let disk = StorageUsage::from_hr_storage("/".to_string(), 4096, 1000, 250).unwrap();
+        assert_eq!(disk.total_bytes, 4_096_000);
+        assert_eq!(disk.used_percent(), Some(25.0));
+        assert_eq!(
+            StorageUsage::from_hr_storage("/data".to_string(), 4096, -1_294_967_296, 0),
+            None
+        );
+    }
+
+    #[test]
+    fn test_mikrotik_temperature() {
+        let walk = vec![(
+            "1.3.6.1.4.1.14988.1.1.3.10.0".to_string(),
+            ObjectValue::Integer(415),
+        )];
+        assert_eq!(mikrotik_temperature(&walk, MTXR_TEMPERATURE), Some(41.5));
+        assert_eq!(
+            mikrotik_temperature(&walk, MTXR_PROCESSOR_TEMPERATURE),
+            None
+        );
+    }
+}
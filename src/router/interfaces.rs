@@ -0,0 +1,183 @@
+use crate::query_engine::{as_int, as_mac, as_string, as_u32, last_index, snmp_query};
+use anyhow::Result;
+use serde::Serialize;
+use std::collections::HashMap;
+
+const IF_TABLE: &str = "1.3.6.1.2.1.2.2.1";
+const IF_X_TABLE: &str = "1.3.6.1.2.1.31.1.1.1";
+
+/// ifAdminStatus / ifOperStatus values
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize)]
+pub enum IfStatus {
+    Up,
+    Down,
+    Testing,
+    #[default]
+    Unknown,
+    Dormant,
+    NotPresent,
+    LowerLayerDown,
+}
+
+impl From<i32> for IfStatus {
+    fn from(value: i32) -> Self {
+        match value {
+            1 => Self::Up,
+            2 => Self::Down,
+            3 => Self::Testing,
+            5 => Self::Dormant,
+            6 => Self::NotPresent,
+            7 => Self::LowerLayerDown,
+            _ => Self::Unknown,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Default, Serialize)]
+pub struct Interface {
+    // ifTable .1
+    pub index: i32,
+    // ifTable .2
+    pub description: String,
+    // ifTable .3 (IANAifType)
+    pub if_type: i32,
+    // ifTable .4
+    pub mtu: i32,
+    // ifXTable .15 (ifHighSpeed), or ifTable .5 (ifSpeed) scaled down
+    pub speed_mbps: u64,
+    // ifTable .6
+    pub phys_address: String,
+    // ifTable .7
+    pub admin_status: IfStatus,
+    // ifTable .8
+    pub oper_status: IfStatus,
+    // ifXTable .1
+    pub name: String,
+    // ifXTable .18
+    pub alias: String,
+}
+
+impl Interface {
+    /// A short human name: ifName if the device provides one, otherwise
+    /// ifDescr.
+    pub fn short_name(&self) -> &str {
+        if self.name.is_empty() {
+            &self.description
+        } else {
+            &self.name
+        }
+    }
+
+    /// Human-readable speed, e.g. "100 Mbps" or "10 Gbps".
+    pub fn speed(&self) -> String {
+        if self.speed_mbps >= 1000 && self.speed_mbps.is_multiple_of(1000) {
+            format!("{} Gbps", self.speed_mbps / 1000)
+        } else {
+            format!("{} Mbps", self.speed_mbps)
+        }
+    }
+
+    /// e.g. "ether3 (Uplink to Tower-7, 1 Gbps)"
+    pub fn label(&self) -> String {
+        let mut details = Vec::new();
+        if !self.alias.is_empty() {
+            details.push(self.alias.clone());
+        }
+        if self.speed_mbps > 0 {
+            details.push(self.speed());
+        }
+        if details.is_empty() {
+            self.short_name().to_string()
+        } else {
+            format!("{} ({})", self.short_name(), details.join(", "))
+        }
+    }
+}
+
+#[derive(Debug, Default, Serialize)]
+pub struct Interfaces {
+    pub interfaces: Vec<Interface>,
+}
+
+impl Interfaces {
+    /// Find an interface by ifIndex.
+    pub fn get(&self, index: i32) -> Option<&Interface> {
+        self.interfaces.iter().find(|i| i.index == index)
+    }
+
+    pub(crate) async fn from_snmp(ip_address: &str, community: &str) -> Result<Self> {
+        let if_table = snmp_query(ip_address, community, IF_TABLE).await?;
+        let mut interfaces: HashMap<i32, Interface> = HashMap::new();
+
+        for (oid, val) in if_table {
+            let index = last_index(&oid)?;
+            let iface = interfaces.entry(index).or_insert_with(|| Interface {
+                index,
+                ..Default::default()
+            });
+            if oid.starts_with("1.3.6.1.2.1.2.2.1.2.") {
+                iface.description = as_string(&val).unwrap_or_default();
+            } else if oid.starts_with("1.3.6.1.2.1.2.2.1.3.") {
+                iface.if_type = as_int(&val)?;
+            } else if oid.starts_with("1.3.6.1.2.1.2.2.1.4.") {
+                iface.mtu = as_int(&val)?;
+            } else if oid.starts_with("1.3.6.1.2.1.2.2.1.5.") {
+                // ifSpeed is in bits per second, and saturates at 4.2 Gbps
+                iface.speed_mbps = as_u32(&val)? as u64 / 1_000_000;
+            } else if oid.starts_with("1.3.6.1.2.1.2.2.1.6.") {
+                iface.phys_address = as_mac(&val).unwrap_or_default();
+            } else if oid.starts_with("1.3.6.1.2.1.2.2.1.7.") {
+                iface.admin_status = as_int(&val)?.into();
+            } else if oid.starts_with("1.3.6.1.2.1.2.2.1.8.") {
+                iface.oper_status = as_int(&val)?.into();
+            }
+        }
+
+        // ifXTable is optional (SNMPv1-era agents don't have it)
+        match snmp_query(ip_address, community, IF_X_TABLE).await {
+            Ok(if_x_table) => {
+                for (oid, val) in if_x_table {
+                    let index = last_index(&oid)?;
+                    let Some(iface) = interfaces.get_mut(&index) else {
+                        continue;
+                    };
+                    if oid.starts_with("1.3.6.1.2.1.31.1.1.1.1.") {
+                        iface.name = as_string(&val).unwrap_or_default();
+                    } else if oid.starts_with("1.3.6.1.2.1.31.1.1.1.15.") {
+                        let high_speed = as_u32(&val)? as u64;
+                        if high_speed > 0 {
+                            iface.speed_mbps = high_speed;
+                        }
+                    } else if oid.starts_with("1.3.6.1.2.1.31.1.1.1.18.") {
+                        iface.alias = as_string(&val).unwrap_or_default();
+                    }
+                }
+            }
+            Err(e) => tracing::info!("No ifXTable on {ip_address}: {e}"),
+        }
+
+        let mut interfaces: Vec<Interface> = interfaces.into_values().collect();
+        interfaces.sort_by_key(|i| i.index);
+        Ok(Self { interfaces })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_interface_label() {
+        let mut iface = Interface {
+            index: 12,
+            description: "ether3".to_string(),
+            speed_mbps: 1000,
+            ..Default::default()
+        };
+        assert_eq!(iface.label(), "ether3 (1 Gbps)");
+        iface.name = "ether3-uplink".to_string();
+        iface.alias = "Uplink to Tower-7".to_string();
+        iface.speed_mbps = 100;
+        assert_eq!(iface.label(), "ether3-uplink (Uplink to Tower-7, 100 Mbps)");
+    }
+}
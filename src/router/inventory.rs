@@ -0,0 +1,222 @@
+use crate::query_engine::{as_int, as_string, oid_index, snmp_query};
+use anyhow::Result;
+use serde::Serialize;
+use std::collections::{BTreeMap, BTreeSet};
+
+// ENTITY-MIB entPhysicalTable
+const ENT_PHYSICAL_TABLE: &str = "1.3.6.1.2.1.47.1.1.1.1";
+
+/// entPhysicalClass
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize)]
+pub enum PhysicalClass {
+    #[default]
+    Other,
+    Unknown,
+    Chassis,
+    Backplane,
+    Container,
+    PowerSupply,
+    Fan,
+    Sensor,
+    Module,
+    Port,
+    Stack,
+    Cpu,
+}
+
+impl From<i32> for PhysicalClass {
+    fn from(value: i32) -> Self {
+        match value {
+            2 => Self::Unknown,
+            3 => Self::Chassis,
+            4 => Self::Backplane,
+            5 => Self::Container,
+            6 => Self::PowerSupply,
+            7 => Self::Fan,
+            8 => Self::Sensor,
+            9 => Self::Module,
+            10 => Self::Port,
+            11 => Self::Stack,
+            12 => Self::Cpu,
+            _ => Self::Other,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Default, Serialize)]
+pub struct PhysicalEntity {
+    // entPhysicalIndex
+    pub index: u32,
+    // .2 entPhysicalDescr
+    pub description: String,
+    // .4 entPhysicalContainedIn (0 = top level)
+    pub contained_in: u32,
+    // .5 entPhysicalClass
+    pub class: PhysicalClass,
+    // .7 entPhysicalName
+    pub name: String,
+    // .8 entPhysicalHardwareRev
+    pub hardware_revision: String,
+    // .9 entPhysicalFirmwareRev
+    pub firmware_revision: String,
+    // .10 entPhysicalSoftwareRev
+    pub software_revision: String,
+    // .11 entPhysicalSerialNum
+    pub serial_number: String,
+    // .12 entPhysicalMfgName
+    pub manufacturer: String,
+    // .13 entPhysicalModelName
+    pub model_name: String,
+    // .16 entPhysicalIsFRU
+    pub field_replaceable: bool,
+}
+
+impl PhysicalEntity {
+    /// Is this worth listing in an asset inventory? Anything with a serial
+    /// number or model name, or that can be replaced in the field.
+    pub fn is_asset(&self) -> bool {
+        !self.serial_number.is_empty() || !self.model_name.is_empty() || self.field_replaceable
+    }
+}
+
+/// The router's physical components, from ENTITY-MIB.
+#[derive(Debug, Default, Serialize)]
+pub struct Inventory {
+    pub entities: Vec<PhysicalEntity>,
+}
+
+impl Inventory {
+    /// The top-level chassis, if the device reports one.
+    pub fn chassis(&self) -> Option<&PhysicalEntity> {
+        self.entities
+            .iter()
+            .find(|e| e.class == PhysicalClass::Chassis)
+    }
+
+    /// Entities directly contained in entity `index` (0 for the top level).
+    pub fn children(&self, index: u32) -> impl Iterator<Item = &PhysicalEntity> {
+        self.entities
+            .iter()
+            .filter(move |e| e.contained_in == index && e.index != index)
+    }
+
+    /// Entities in containment order (each parent before its children),
+    /// with their depth in the tree. Entities with a missing parent are
+    /// roots, and so is the first member of each containment loop.
+    pub fn tree(&self) -> Vec<(usize, &PhysicalEntity)> {
+        let is_root = |e: &&PhysicalEntity| {
+            e.contained_in == 0 || !self.entities.iter().any(|p| p.index == e.contained_in)
+        };
+        // Anything left after the real roots is only reachable from inside a
+        // loop in a broken agent
+        let roots = self
+            .entities
+            .iter()
+            .filter(is_root)
+            .chain(self.entities.iter().filter(|e| !is_root(e)));
+        let mut result = Vec::new();
+        let mut seen = BTreeSet::new();
+        for root in roots {
+            let mut stack = vec![(0, root)];
+            while let Some((depth, entity)) = stack.pop() {
+                if !seen.insert(entity.index) {
+                    continue;
+                }
+                result.push((depth, entity));
+                let children: Vec<&PhysicalEntity> = self.children(entity.index).collect();
+                stack.extend(children.into_iter().rev().map(|c| (depth + 1, c)));
+            }
+        }
+        result
+    }
+
+    pub(crate) async fn from_snmp(ip_address: &str, community: &str) -> Result<Self> {
+        let table = snmp_query(ip_address, community, ENT_PHYSICAL_TABLE).await?;
+        let mut entities: BTreeMap<u32, PhysicalEntity> = BTreeMap::new();
+        for (oid, val) in table {
+            // column.entPhysicalIndex
+            let Some(index) = oid_index(&oid, ENT_PHYSICAL_TABLE) else {
+                continue;
+            };
+            if index.len() != 2 {
+                continue;
+            }
+            let entity = entities.entry(index[1]).or_insert_with(|| PhysicalEntity {
+                index: index[1],
+                ..Default::default()
+            });
+            let text = || as_string(&val).unwrap_or_default().trim().to_string();
+            match index[0] {
+                2 => entity.description = text(),
+                4 => entity.contained_in = as_int(&val)? as u32,
+                5 => entity.class = as_int(&val)?.into(),
+                7 => entity.name = text(),
+                8 => entity.hardware_revision = text(),
+                9 => entity.firmware_revision = text(),
+                10 => entity.software_revision = text(),
+                11 => entity.serial_number = text(),
+                12 => entity.manufacturer = text(),
+                13 => entity.model_name = text(),
+                // TruthValue: 1 = true, 2 = false
+                16 => entity.field_replaceable = as_int(&val)? == 1,
+                _ => {}
+            }
+        }
+        Ok(Self {
+            entities: entities.into_values().collect(),
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn entity(index: u32, contained_in: u32, class: PhysicalClass) -> PhysicalEntity {
+        PhysicalEntity {
+            index,
+            contained_in,
+            class,
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn test_containment_tree() {
+        let inventory = Inventory {
+            entities: vec![
+                entity(1, 0, PhysicalClass::Chassis),
+                entity(2, 1, PhysicalClass::Container),
+                entity(3, 2, PhysicalClass::Module),
+                entity(4, 1, PhysicalClass::PowerSupply),
+            ],
+        };
+        let tree: Vec<(usize, u32)> = inventory
+            .tree()
+            .into_iter()
+            .map(|(depth, e)| (depth, e.index))
+            .collect();
+        assert_eq!(tree, vec![(0, 1), (1, 2), (2, 3), (1, 4)]);
+        assert_eq!(inventory.chassis().unwrap().index, 1);
+    }
+
+    #[test]
+    fn test_containment_loop() {
+        let inventory = Inventory {
+            entities: vec![
+                entity(1, 0, PhysicalClass::Chassis),
+                entity(2, 3, PhysicalClass::Module),
+                entity(3, 2, PhysicalClass::Module),
+                entity(4, 4, PhysicalClass::Port),
+                // Its parent isn't in the table
+                entity(5, 9, PhysicalClass::Port),
+            ],
+        };
+        let tree: Vec<(usize, u32)> = inventory
+            .tree()
+            .into_iter()
+            .map(|(depth, e)| (depth, e.index))
+            .collect();
+        assert_eq!(tree, vec![(0, 1), (0, 5), (0, 2), (1, 3), (0, 4)]);
+    }
+}
@@ -1,9 +1,10 @@
 use crate::query_engine::{as_int, as_ip, snmp_query};
 use anyhow::{bail, Result};
 use ipnetwork::ip_mask_to_prefix;
+use serde::Serialize;
 use std::{collections::HashMap, net::IpAddr};
 
-#[derive(Debug)]
+#[derive(Debug, Clone, Default, Serialize)]
 pub struct IpRoutes {
     pub routes: Vec<CidrEntry>,
 }
@@ -13,7 +14,7 @@ const CIDR_TABLE: &str = "1.3.6.1.2.1.4.24.4.";
 
 const INET_ROUTE_TABLE: &str = "1.3.6.1.2.1.4.21.1.";
 
-#[derive(Debug)]
+#[derive(Debug, Clone, Serialize)]
 pub struct CidrEntry {
     // .1 = destination
     pub destination: IpAddr,
@@ -38,7 +39,22 @@ pub struct CidrEntry {
     // .16 = Route Status
 }
 
+impl CidrEntry {
+    /// The destination network, if the netmask was retrieved.
+    pub fn ip_network(&self) -> Option<ipnetwork::IpNetwork> {
+        ipnetwork::IpNetwork::new(self.destination, self.netmask).ok()
+    }
+}
+
 impl IpRoutes {
+    /// Find the most specific route covering `address`.
+    pub fn longest_match(&self, address: IpAddr) -> Option<&CidrEntry> {
+        self.routes
+            .iter()
+            .filter(|r| r.ip_network().is_some_and(|net| net.contains(address)))
+            .max_by_key(|r| r.netmask)
+    }
+
     fn ip_from_oid(oid: &str) -> Result<IpAddr> {
         let oid_split = oid.split('.').collect::<Vec<&str>>();
         let idx = 11;
@@ -106,7 +122,7 @@ impl IpRoutes {
         }
 
         Ok(Self {
-            routes: routes.into_iter().map(|(_, v)| v).collect(),
+            routes: routes.into_values().collect(),
         })
     }
 
@@ -131,18 +147,18 @@ impl IpRoutes {
                     if_index: -1,
                 };
                 routes.insert(route_ip, new_route);
-            } else if oid.starts_with("1.3.6.1.2.1.4.21.1.2.") {
-                let ip_from_oid = oid[21..].parse::<IpAddr>()?;
+            } else if let Some(suffix) = oid.strip_prefix("1.3.6.1.2.1.4.21.1.2.") {
+                let ip_from_oid = suffix.parse::<IpAddr>()?;
                 if let Some(route) = routes.get_mut(&ip_from_oid) {
                     route.if_index = as_int(&val)?;
                 }
-            } else if oid.starts_with("1.3.6.1.2.1.4.21.1.7.") {
-                let ip_from_oid = oid[21..].parse::<IpAddr>()?;
+            } else if let Some(suffix) = oid.strip_prefix("1.3.6.1.2.1.4.21.1.7.") {
+                let ip_from_oid = suffix.parse::<IpAddr>()?;
                 if let Some(route) = routes.get_mut(&ip_from_oid) {
                     route.next_hop = as_ip(&val)?;
                 }
-            } else if oid.starts_with("1.3.6.1.2.1.4.21.1.11.") {
-                let ip_from_oid = oid[22..].parse::<IpAddr>()?;
+            } else if let Some(suffix) = oid.strip_prefix("1.3.6.1.2.1.4.21.1.11.") {
+                let ip_from_oid = suffix.parse::<IpAddr>()?;
                 if let Some(route) = routes.get_mut(&ip_from_oid) {
                     let mask = as_ip(&val)?;
                     let cidr = ip_mask_to_prefix(mask)?;
@@ -152,7 +168,7 @@ impl IpRoutes {
         }
 
         Ok(Self {
-            routes: routes.into_iter().map(|(_, v)| v).collect(),
+            routes: routes.into_values().collect(),
         })
     }
 }
@@ -1,13 +1,14 @@
 use crate::query_engine::{as_cidr, as_int, as_ip, snmp_query};
 use anyhow::Result;
+use serde::Serialize;
 use std::{collections::HashMap, net::IpAddr};
 
-#[derive(Debug)]
+#[derive(Debug, Serialize)]
 pub struct IpTable {
     pub ips: Vec<IpAddress>,
 }
 
-#[derive(Debug)]
+#[derive(Debug, Serialize)]
 pub struct IpAddress {
     pub address: IpAddr,
     pub interface_index: i32,
@@ -15,8 +16,9 @@ pub struct IpAddress {
 }
 
 impl IpAddress {
-    pub fn ip_network(&self) -> ipnetwork::IpNetwork {
-        ipnetwork::IpNetwork::new(self.address, self.cidr_mask).unwrap()
+    /// The connected network, if the netmask was retrieved and is valid.
+    pub fn ip_network(&self) -> Option<ipnetwork::IpNetwork> {
+        ipnetwork::IpNetwork::new(self.address, self.cidr_mask).ok()
     }
 }
 
@@ -37,15 +39,15 @@ impl IpTable {
                         cidr_mask: 255,
                     },
                 );
-            } else if oid.starts_with("1.3.6.1.2.1.4.20.1.2.") {
+            } else if let Some(suffix) = oid.strip_prefix("1.3.6.1.2.1.4.20.1.2.") {
                 // Interface index
-                let ip: IpAddr = oid[21..].parse()?;
+                let ip: IpAddr = suffix.parse()?;
                 if let Some(iface) = ips.get_mut(&ip) {
                     iface.interface_index = as_int(&val)?;
                 }
-            } else if oid.starts_with("1.3.6.1.2.1.4.20.1.3.") {
+            } else if let Some(suffix) = oid.strip_prefix("1.3.6.1.2.1.4.20.1.3.") {
                 // Netmask
-                let ip: IpAddr = oid[21..].parse()?;
+                let ip: IpAddr = suffix.parse()?;
                 if let Some(iface) = ips.get_mut(&ip) {
                     iface.cidr_mask = as_cidr(&val).unwrap_or_default();
                 }
@@ -53,7 +55,7 @@ impl IpTable {
         }
 
         Ok(IpTable {
-            ips: ips.into_iter().map(|(_, v)| v).collect(),
+            ips: ips.into_values().collect(),
         })
     }
 }
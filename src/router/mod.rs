@@ -1,24 +1,133 @@
+use crate::query_engine;
 use anyhow::Result;
-use tracing::{debug, error};
+use serde::Serialize;
+use std::{collections::BTreeMap, future::Future, net::IpAddr};
+use tracing::{debug, error, warn};
 mod system_info;
-use system_info::SystemInfo;
+pub use system_info::SystemInfo;
 mod connection;
 use connection::Connection;
 mod ip_table;
-use crate::router::ip_routes::IpRoutes;
-use ip_table::IpTable;
+pub use ip_routes::{CidrEntry, IpRoutes};
+pub use ip_table::{IpAddress, IpTable};
+mod interfaces;
 mod ip_routes;
+pub use interfaces::{Interface, Interfaces};
+mod neighbors;
+pub use neighbors::{Neighbor, NeighborProtocol, Neighbors};
+mod arp_table;
+pub use arp_table::{ArpEntry, ArpTable};
+mod bridge;
+pub use bridge::Bridge;
+mod bgp;
+pub use bgp::{Bgp, BgpPeer, BgpPeerState};
+mod ospf;
+pub use ospf::{Ospf, OspfNeighbor, OspfNeighborState};
+mod inventory;
+pub use inventory::{Inventory, PhysicalEntity};
+mod next_hop;
+pub use next_hop::{NextHopResolution, ResolutionOutcome};
+mod counters;
+pub use counters::{poll_traffic, InterfaceRates, Traffic};
+mod fingerprint;
+pub use fingerprint::{Fingerprint, Vendor};
+mod health;
+pub use health::Health;
+#[cfg(test)]
+pub(crate) mod fixtures;
+mod profile;
+pub use profile::{ProfileConfig, Table, VendorProfile};
+mod upstream;
+pub use upstream::{UpstreamRoute, UpstreamSource};
+mod vrf;
+pub use vrf::{Vrf, Vrfs};
+mod wireless;
+pub use wireless::{Wireless, WirelessLink, WirelessTable};
 
-pub async fn router_builder(ip_address: String, community: String) -> Result<Router> {
+pub async fn router_builder(
+    ip_address: String,
+    community: String,
+    vrf: Option<String>,
+) -> Result<Router> {
     debug!("Querying {ip_address} for SNMP information");
     let connection = Connection {
         snmp_address: ip_address.to_string(),
         snmp_community: community.to_string(),
+        vrf: vrf.clone(),
+        site: None,
     };
-    let (system_info, ip_table, ip_routes) = tokio::join!(
-        SystemInfo::from_snmp(&ip_address, &community),
+    // Identify the device first, so the bigger walks can be tuned for it
+    let system_info = SystemInfo::from_snmp(&ip_address, &community).await;
+    let mut fingerprint = system_info
+        .as_ref()
+        .map(Fingerprint::identify)
+        .unwrap_or_default();
+    let profile = profile::select(
+        system_info.as_ref().unwrap_or(&SystemInfo::default()),
+        &fingerprint,
+    );
+    debug!("Using the {} profile for {ip_address}", profile.name());
+    query_engine::set_query_settings(&ip_address, profile.query_settings());
+    let walks = |table| profile.walks(table);
+    let (
+        ip_table,
+        ip_routes,
+        interfaces,
+        neighbors,
+        arp_table,
+        bridge,
+        bgp,
+        ospf,
+        inventory,
+        health,
+        vrfs,
+        wireless,
+        extra_tables,
+    ) = tokio::join!(
         IpTable::from_snmp(&ip_address, &community),
-        IpRoutes::from_snmp(&ip_address, &community)
+        async {
+            if profile.old_route_table() {
+                IpRoutes::from_old_table(&ip_address, &community).await
+            } else {
+                IpRoutes::from_snmp(&ip_address, &community).await
+            }
+        },
+        walk(
+            walks(Table::Interfaces),
+            Interfaces::from_snmp(&ip_address, &community)
+        ),
+        walk(
+            walks(Table::Neighbors),
+            Neighbors::from_snmp(&ip_address, &community)
+        ),
+        walk(
+            walks(Table::Arp),
+            ArpTable::from_snmp(&ip_address, &community)
+        ),
+        walk(
+            walks(Table::Bridge),
+            Bridge::from_snmp(&ip_address, &community)
+        ),
+        walk(walks(Table::Bgp), Bgp::from_snmp(&ip_address, &community)),
+        walk(walks(Table::Ospf), Ospf::from_snmp(&ip_address, &community)),
+        walk(
+            walks(Table::Inventory),
+            Inventory::from_snmp(&ip_address, &community)
+        ),
+        walk(
+            walks(Table::Health),
+            Health::from_snmp(&ip_address, &community, fingerprint.vendor)
+        ),
+        // A VRF asked for in the router list is needed whatever the profile says
+        walk(
+            walks(Table::Vrfs) || vrf.is_some(),
+            Vrfs::from_snmp(&ip_address, &community, vrf.as_deref())
+        ),
+        walk(
+            walks(Table::Wireless),
+            Wireless::from_snmp(&ip_address, &community, fingerprint.vendor)
+        ),
+        profile::walk_extra_tables(&ip_address, &community, profile.extra_tables()),
     );
     debug!("Finished querying {ip_address} for SNMP information");
 
@@ -32,18 +141,132 @@ pub async fn router_builder(ip_address: String, community: String) -> Result<Rou
         error!("Failed to query {ip_address} for IP routes information");
     }
 
+    let inventory = optional(&ip_address, "hardware inventory", inventory);
+    let vrfs = optional(&ip_address, "VRFs", vrfs);
+    let mut ip_routes = ip_routes?;
+    if let Some(name) = &vrf {
+        // Map the router by the VRF it was asked for, rather than the global table
+        // If the VRF can't be read, fall back to the global table; the
+        // topology reports it as a finding
+        match vrfs.get(name) {
+            Some(vrf) if !vrf.routes.routes.is_empty() => ip_routes = vrf.routes.clone(),
+            _ => warn!("No routes found in VRF {name} on {ip_address}, using the global table"),
+        }
+    }
+    fingerprint.refine(&inventory);
+    debug!("{ip_address} identified as {fingerprint:?}");
+
     Ok(Router {
         connection,
         system_info: system_info?,
         ip_table: ip_table?,
-        ip_routes: ip_routes?,
+        ip_routes,
+        interfaces: optional(&ip_address, "interfaces", interfaces),
+        neighbors: optional(&ip_address, "neighbors", neighbors),
+        arp_table: optional(&ip_address, "ARP table", arp_table),
+        bridge: optional(&ip_address, "bridge forwarding table", bridge),
+        bgp: optional(&ip_address, "BGP", bgp),
+        ospf: optional(&ip_address, "OSPF", ospf),
+        inventory,
+        fingerprint,
+        health: optional(&ip_address, "health", health),
+        vrfs,
+        wireless: optional(&ip_address, "wireless links", wireless),
+        traffic: Traffic::default(),
+        profile: profile.name().to_string(),
+        extra_tables,
+    })
+}
+
+/// Run `load` only if the profile walks its table; otherwise the table is
+/// left empty.
+async fn walk<T: Default>(enabled: bool, load: impl Future<Output = Result<T>>) -> Result<T> {
+    if enabled {
+        load.await
+    } else {
+        Ok(T::default())
+    }
+}
+
+/// Tables beyond the core three are nice to have: log a failure and carry
+/// on with an empty table rather than dropping the whole router.
+fn optional<T: Default>(ip_address: &str, what: &str, result: Result<T>) -> T {
+    result.unwrap_or_else(|e| {
+        warn!("Unable to query {ip_address} for {what}: {e}");
+        T::default()
     })
 }
 
-#[derive(Debug)]
+#[derive(Debug, Serialize)]
 pub struct Router {
     pub connection: Connection,
     pub system_info: SystemInfo,
     pub ip_table: IpTable,
     pub ip_routes: IpRoutes,
+    pub interfaces: Interfaces,
+    pub neighbors: Neighbors,
+    pub arp_table: ArpTable,
+    pub bridge: Bridge,
+    pub bgp: Bgp,
+    pub ospf: Ospf,
+    pub inventory: Inventory,
+    pub fingerprint: Fingerprint,
+    pub health: Health,
+    pub vrfs: Vrfs,
+    pub wireless: Wireless,
+    /// The vendor profile the router was collected with
+    pub profile: String,
+    /// Extra vendor tables the profile asked for: name -> (OID, value)
+    pub extra_tables: BTreeMap<String, Vec<(String, String)>>,
+    /// Interface rates, if traffic polling is enabled
+    pub traffic: Traffic,
+}
+
+impl Router {
+    /// The site the router belongs to: its site tag from the router list if
+    /// it has one, otherwise sysLocation.
+    pub fn site(&self) -> &str {
+        self.connection
+            .site
+            .as_deref()
+            .unwrap_or(&self.system_info.location)
+    }
+
+    /// The interface that `address` is reached through: the interface holding
+    /// a connected subnet containing it, or failing that the interface of the
+    /// most specific route to it.
+    pub fn interface_towards(&self, address: IpAddr) -> Option<&Interface> {
+        let index = self
+            .ip_table
+            .ips
+            .iter()
+            .filter(|ip| ip.ip_network().is_some_and(|net| net.contains(address)))
+            .max_by_key(|ip| ip.cidr_mask)
+            .map(|ip| ip.interface_index)
+            .or_else(|| self.ip_routes.longest_match(address).map(|r| r.if_index))?;
+        self.interfaces.get(index)
+    }
+
+    /// The local interface a neighbor was seen on.
+    pub fn neighbor_interface(&self, neighbor: &Neighbor) -> Option<&Interface> {
+        if !neighbor.local_port_name.is_empty() {
+            let by_name = self.interfaces.interfaces.iter().find(|i| {
+                i.name == neighbor.local_port_name || i.description == neighbor.local_port_name
+            });
+            if by_name.is_some() {
+                return by_name;
+            }
+        }
+        self.interfaces.get(neighbor.local_port)
+    }
+
+    /// Does any of this router's interfaces have the MAC address `mac`?
+    pub fn has_mac(&self, mac: &str) -> bool {
+        !mac.is_empty()
+            && self
+                .interfaces
+                .interfaces
+                .iter()
+                .any(|i| i.phys_address.eq_ignore_ascii_case(mac))
+    }
 }
@@ -0,0 +1,112 @@
+use super::{Neighbor, NeighborProtocol};
+use crate::query_engine::{as_bytes, as_int, as_string, oid_index, snmp_query};
+use anyhow::Result;
+use std::{
+    collections::BTreeMap,
+    net::{IpAddr, Ipv4Addr, Ipv6Addr},
+};
+
+// CISCO-CDP-MIB cdpCacheTable
+const CDP_CACHE_TABLE: &str = "1.3.6.1.4.1.9.9.23.1.2.1.1";
+
+// CiscoNetworkProtocol values for the address types we can decode
+const CDP_ADDRESS_IP: i32 = 1;
+const CDP_ADDRESS_IPV6: i32 = 20;
+
+/// Decode a cdpCacheAddress-style octet string given its address type.
+fn decode_address(address_type: i32, bytes: &[u8]) -> Option<IpAddr> {
+    match (address_type, bytes.len()) {
+        (CDP_ADDRESS_IP, 4) => Some(IpAddr::V4(Ipv4Addr::new(
+            bytes[0], bytes[1], bytes[2], bytes[3],
+        ))),
+        (CDP_ADDRESS_IPV6, 16) => {
+            let octets: [u8; 16] = bytes.try_into().ok()?;
+            Some(IpAddr::V6(Ipv6Addr::from(octets)))
+        }
+        _ => None,
+    }
+}
+
+/// A cdpCacheEntry, with the address type columns held until their
+/// matching address column arrives.
+struct CacheRow {
+    neighbor: Neighbor,
+    // cdpCacheAddressType (.3), cdpCachePrimaryMgmtAddrType (.15),
+    // cdpCacheSecondaryMgmtAddrType (.17)
+    address_types: BTreeMap<u32, i32>,
+}
+
+pub(crate) async fn from_snmp(ip_address: &str, community: &str) -> Result<Vec<Neighbor>> {
+    // Keyed by (cdpCacheIfIndex, cdpCacheDeviceIndex)
+    let mut rows: BTreeMap<(u32, u32), CacheRow> = BTreeMap::new();
+    for (oid, val) in snmp_query(ip_address, community, CDP_CACHE_TABLE).await? {
+        // column.ifIndex.deviceIndex
+        let Some(index) = oid_index(&oid, CDP_CACHE_TABLE) else {
+            continue;
+        };
+        if index.len() != 3 {
+            continue;
+        }
+        let row = rows
+            .entry((index[1], index[2]))
+            .or_insert_with(|| CacheRow {
+                // CDP indexes the cache by ifIndex directly
+                neighbor: Neighbor::new(NeighborProtocol::Cdp, index[1] as i32),
+                address_types: BTreeMap::new(),
+            });
+        match index[0] {
+            3 | 15 | 17 => {
+                row.address_types.insert(index[0], as_int(&val)?);
+            }
+            4 | 16 | 18 => {
+                let address_type = row
+                    .address_types
+                    .get(&(index[0] - 1))
+                    .copied()
+                    .unwrap_or(CDP_ADDRESS_IP);
+                if let Some(address) = decode_address(address_type, as_bytes(&val)?) {
+                    if !row.neighbor.management_addresses.contains(&address) {
+                        row.neighbor.management_addresses.push(address);
+                    }
+                }
+            }
+            // cdpCacheVersion
+            5 => row.neighbor.system_description = as_string(&val).unwrap_or_default(),
+            // cdpCacheDeviceId
+            6 => {
+                let device_id = as_string(&val).unwrap_or_default();
+                row.neighbor.chassis_id = device_id.clone();
+                row.neighbor.system_name = device_id;
+            }
+            // cdpCacheDevicePort
+            7 => row.neighbor.port_id = as_string(&val).unwrap_or_default(),
+            // cdpCachePlatform
+            8 => row.neighbor.platform = as_string(&val).unwrap_or_default(),
+            _ => {}
+        }
+    }
+
+    Ok(rows.into_values().map(|row| row.neighbor).collect())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_decode_cdp_address() {
+        assert_eq!(
+            decode_address(CDP_ADDRESS_IP, &[10, 1, 2, 3]),
+            Some("10.1.2.3".parse().unwrap())
+        );
+        let mut v6 = [0u8; 16];
+        v6[0] = 0x20;
+        v6[1] = 0x01;
+        v6[15] = 1;
+        assert_eq!(
+            decode_address(CDP_ADDRESS_IPV6, &v6),
+            Some("2001::1".parse().unwrap())
+        );
+        assert_eq!(decode_address(CDP_ADDRESS_IP, &[10, 1, 2]), None);
+    }
+}
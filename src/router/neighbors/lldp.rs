@@ -0,0 +1,286 @@
+use super::{Neighbor, NeighborProtocol};
+use crate::csnmp::ObjectValue;
+use crate::query_engine::{as_bytes, as_int, as_string, format_mac, oid_index, snmp_query};
+use anyhow::Result;
+use std::{
+    collections::BTreeMap,
+    net::{IpAddr, Ipv4Addr, Ipv6Addr},
+};
+
+// LLDP-MIB (IEEE 802.1AB-2005)
+const LLDP_REM_TABLE: &str = "1.0.8802.1.1.2.1.4.1.1";
+const LLDP_REM_MAN_ADDR_TABLE: &str = "1.0.8802.1.1.2.1.4.2.1";
+const LLDP_LOC_PORT_TABLE: &str = "1.0.8802.1.1.2.1.3.7.1";
+
+// LLDP-V2-MIB (IEEE 802.1AB-2009)
+const LLDP_V2_REM_TABLE: &str = "1.3.111.2.802.1.1.13.1.4.1.1";
+const LLDP_V2_REM_MAN_ADDR_TABLE: &str = "1.3.111.2.802.1.1.13.1.4.2.1";
+
+// LldpChassisIdSubtype / LldpPortIdSubtype values we decode specially
+const CHASSIS_ID_MAC: i32 = 4;
+const CHASSIS_ID_NETWORK_ADDRESS: i32 = 5;
+const PORT_ID_MAC: i32 = 3;
+const PORT_ID_NETWORK_ADDRESS: i32 = 4;
+
+/// Load LLDP neighbors, preferring LLDP-MIB and falling back to LLDP-V2-MIB.
+pub(crate) async fn from_snmp(ip_address: &str, community: &str) -> Result<Vec<Neighbor>> {
+    let neighbors = from_v1(ip_address, community).await.unwrap_or_default();
+    if !neighbors.is_empty() {
+        return Ok(neighbors);
+    }
+    from_v2(ip_address, community).await
+}
+
+/// A raw remote-table row, before subtype decoding.
+#[derive(Default)]
+struct RemoteRow {
+    chassis_id_subtype: i32,
+    chassis_id: Vec<u8>,
+    port_id_subtype: i32,
+    port_id: Vec<u8>,
+    port_description: String,
+    system_name: String,
+    system_description: String,
+}
+
+impl RemoteRow {
+    /// Apply column `column` of lldpRemEntry / lldpV2RemEntry, where
+    /// `offset` is the column number of ChassisIdSubtype in that table.
+    fn set(&mut self, column: u32, offset: u32, value: &ObjectValue) -> Result<()> {
+        match column.checked_sub(offset) {
+            Some(0) => self.chassis_id_subtype = as_int(value)?,
+            Some(1) => self.chassis_id = as_bytes(value)?.to_vec(),
+            Some(2) => self.port_id_subtype = as_int(value)?,
+            Some(3) => self.port_id = as_bytes(value)?.to_vec(),
+            Some(4) => self.port_description = as_string(value).unwrap_or_default(),
+            Some(5) => self.system_name = as_string(value).unwrap_or_default(),
+            Some(6) => self.system_description = as_string(value).unwrap_or_default(),
+            _ => {}
+        }
+        Ok(())
+    }
+
+    fn into_neighbor(self, local_port: i32) -> Neighbor {
+        Neighbor {
+            protocol: NeighborProtocol::Lldp,
+            local_port,
+            local_port_name: String::new(),
+            chassis_id: decode_id(
+                self.chassis_id_subtype,
+                &self.chassis_id,
+                CHASSIS_ID_MAC,
+                CHASSIS_ID_NETWORK_ADDRESS,
+            ),
+            port_id: decode_id(
+                self.port_id_subtype,
+                &self.port_id,
+                PORT_ID_MAC,
+                PORT_ID_NETWORK_ADDRESS,
+            ),
+            port_description: self.port_description,
+            system_name: self.system_name,
+            system_description: self.system_description,
+            platform: String::new(),
+            management_addresses: Vec::new(),
+        }
+    }
+}
+
+/// Decode a chassis or port ID according to its subtype.
+fn decode_id(subtype: i32, bytes: &[u8], mac: i32, network_address: i32) -> String {
+    if subtype == mac && bytes.len() == 6 {
+        format_mac(bytes)
+    } else if subtype == network_address && !bytes.is_empty() {
+        // First byte is the IANA address family
+        decode_address(bytes[0] as u32, &bytes[1..])
+            .map(|ip| ip.to_string())
+            .unwrap_or_else(|| format_mac(bytes))
+    } else {
+        String::from_utf8(bytes.to_vec()).unwrap_or_else(|_| format_mac(bytes))
+    }
+}
+
+/// Decode an address given its IANA address family (1 = IPv4, 2 = IPv6).
+pub(crate) fn decode_address(family: u32, bytes: &[u8]) -> Option<IpAddr> {
+    match (family, bytes.len()) {
+        (1, 4) => Some(IpAddr::V4(Ipv4Addr::new(
+            bytes[0], bytes[1], bytes[2], bytes[3],
+        ))),
+        (2, 16) => {
+            let octets: [u8; 16] = bytes.try_into().ok()?;
+            Some(IpAddr::V6(Ipv6Addr::from(octets)))
+        }
+        _ => None,
+    }
+}
+
+/// Decode the management address embedded in a lldpRemManAddrTable index:
+/// `subtype.length.addr...`
+fn address_from_index(index: &[u32]) -> Option<IpAddr> {
+    let (family, rest) = index.split_first()?;
+    let (length, rest) = rest.split_first()?;
+    let bytes: Vec<u8> = rest
+        .iter()
+        .take(*length as usize)
+        .map(|b| *b as u8)
+        .collect();
+    decode_address(*family, &bytes)
+}
+
+async fn from_v1(ip_address: &str, community: &str) -> Result<Vec<Neighbor>> {
+    // Keyed by (lldpRemLocalPortNum, lldpRemIndex)
+    let mut rows: BTreeMap<(u32, u32), RemoteRow> = BTreeMap::new();
+    for (oid, val) in snmp_query(ip_address, community, LLDP_REM_TABLE).await? {
+        // column.timeMark.localPortNum.remIndex
+        let Some(index) = oid_index(&oid, LLDP_REM_TABLE) else {
+            continue;
+        };
+        if index.len() < 4 {
+            continue;
+        }
+        rows.entry((index[2], index[3]))
+            .or_default()
+            .set(index[0], 4, &val)?;
+    }
+
+    let mut neighbors: BTreeMap<(u32, u32), Neighbor> = rows
+        .into_iter()
+        .map(|(key, row)| (key, row.into_neighbor(key.0 as i32)))
+        .collect();
+
+    // column.timeMark.localPortNum.remIndex.addrSubtype.addrLen.addr...
+    let man_addrs = snmp_query(ip_address, community, LLDP_REM_MAN_ADDR_TABLE)
+        .await
+        .unwrap_or_default();
+    for (oid, _) in man_addrs {
+        let Some(index) = oid_index(&oid, LLDP_REM_MAN_ADDR_TABLE) else {
+            continue;
+        };
+        if index.len() < 5 {
+            continue;
+        }
+        add_management_address(&mut neighbors, (index[2], index[3]), &index[4..]);
+    }
+
+    // Local port names, so the neighbor can be matched to an interface
+    let loc_ports = snmp_query(ip_address, community, LLDP_LOC_PORT_TABLE)
+        .await
+        .unwrap_or_default();
+    for (oid, val) in loc_ports {
+        // column.localPortNum; .3 = lldpLocPortId, .4 = lldpLocPortDesc
+        let Some(index) = oid_index(&oid, LLDP_LOC_PORT_TABLE) else {
+            continue;
+        };
+        if index.len() != 2 || !(index[0] == 3 || index[0] == 4) {
+            continue;
+        }
+        let name = as_string(&val).unwrap_or_default();
+        for neighbor in neighbors
+            .values_mut()
+            .filter(|n| n.local_port == index[1] as i32)
+        {
+            // Prefer the description (usually ifDescr) over the port ID
+            if neighbor.local_port_name.is_empty() || index[0] == 4 {
+                neighbor.local_port_name = name.clone();
+            }
+        }
+    }
+
+    Ok(neighbors.into_values().collect())
+}
+
+async fn from_v2(ip_address: &str, community: &str) -> Result<Vec<Neighbor>> {
+    // Keyed by (lldpV2RemLocalIfIndex, lldpV2RemLocalDestMACAddress, lldpV2RemIndex)
+    let mut rows: BTreeMap<(u32, u32, u32), RemoteRow> = BTreeMap::new();
+    for (oid, val) in snmp_query(ip_address, community, LLDP_V2_REM_TABLE).await? {
+        // column.timeMark.ifIndex.destMacIndex.remIndex
+        let Some(index) = oid_index(&oid, LLDP_V2_REM_TABLE) else {
+            continue;
+        };
+        if index.len() < 5 {
+            continue;
+        }
+        rows.entry((index[2], index[3], index[4]))
+            .or_default()
+            .set(index[0], 5, &val)?;
+    }
+
+    let mut neighbors: BTreeMap<(u32, u32), Neighbor> = rows
+        .into_iter()
+        .map(|((if_index, _, rem_index), row)| {
+            ((if_index, rem_index), row.into_neighbor(if_index as i32))
+        })
+        .collect();
+
+    // column.timeMark.ifIndex.destMacIndex.remIndex.addrSubtype.addrLen.addr...
+    let man_addrs = snmp_query(ip_address, community, LLDP_V2_REM_MAN_ADDR_TABLE)
+        .await
+        .unwrap_or_default();
+    for (oid, _) in man_addrs {
+        let Some(index) = oid_index(&oid, LLDP_V2_REM_MAN_ADDR_TABLE) else {
+            continue;
+        };
+        if index.len() < 6 {
+            continue;
+        }
+        add_management_address(&mut neighbors, (index[2], index[4]), &index[5..]);
+    }
+
+    Ok(neighbors.into_values().collect())
+}
+
+fn add_management_address(
+    neighbors: &mut BTreeMap<(u32, u32), Neighbor>,
+    key: (u32, u32),
+    address_index: &[u32],
+) {
+    let (Some(neighbor), Some(address)) =
+        (neighbors.get_mut(&key), address_from_index(address_index))
+    else {
+        return;
+    };
+    if !neighbor.management_addresses.contains(&address) {
+        neighbor.management_addresses.push(address);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_address_from_index() {
+        assert_eq!(
+            address_from_index(&[1, 4, 10, 0, 0, 1]),
+            Some("10.0.0.1".parse().unwrap())
+        );
+        assert_eq!(address_from_index(&[1, 4, 10, 0]), None);
+    }
+
+    #[test]
+    fn test_decode_id() {
+        let mac = [0x4c, 0x5e, 0x0c, 0x12, 0x34, 0x56];
+        assert_eq!(
+            decode_id(
+                CHASSIS_ID_MAC,
+                &mac,
+                CHASSIS_ID_MAC,
+                CHASSIS_ID_NETWORK_ADDRESS
+            ),
+            "4C:5E:0C:12:34:56"
+        );
+        assert_eq!(
+            decode_id(5, b"ether1", PORT_ID_MAC, PORT_ID_NETWORK_ADDRESS),
+            "ether1"
+        );
+        assert_eq!(
+            decode_id(
+                CHASSIS_ID_NETWORK_ADDRESS,
+                &[1, 192, 168, 0, 1],
+                CHASSIS_ID_MAC,
+                CHASSIS_ID_NETWORK_ADDRESS
+            ),
+            "192.168.0.1"
+        );
+    }
+}
@@ -0,0 +1,93 @@
+use super::{Neighbor, NeighborProtocol};
+use crate::csnmp::ObjectValue;
+use crate::query_engine::{as_int, as_ip, as_mac, as_string, oid_index, snmp_query};
+use anyhow::Result;
+use std::collections::BTreeMap;
+
+// MIKROTIK-MIB mtxrNeighborTable (RouterOS /ip neighbor)
+const MTXR_NEIGHBOR_TABLE: &str = "1.3.6.1.4.1.14988.1.1.11.1.1";
+
+pub(crate) async fn from_snmp(ip_address: &str, community: &str) -> Result<Vec<Neighbor>> {
+    parse(snmp_query(ip_address, community, MTXR_NEIGHBOR_TABLE).await?)
+}
+
+/// Build neighbors from a walk of mtxrNeighborTable.
+fn parse(walk: Vec<(String, ObjectValue)>) -> Result<Vec<Neighbor>> {
+    // Keyed by mtxrNeighborIndex
+    let mut rows: BTreeMap<u32, Neighbor> = BTreeMap::new();
+    for (oid, val) in walk {
+        // column.neighborIndex
+        let Some(index) = oid_index(&oid, MTXR_NEIGHBOR_TABLE) else {
+            continue;
+        };
+        if index.len() != 2 {
+            continue;
+        }
+        let neighbor = rows
+            .entry(index[1])
+            .or_insert_with(|| Neighbor::new(NeighborProtocol::Mndp, -1));
+        match index[0] {
+            // mtxrNeighborIpAddress
+            2 => {
+                let address = as_ip(&val)?;
+                if !address.is_unspecified() {
+                    neighbor.management_addresses.push(address);
+                }
+            }
+            // mtxrNeighborMacAddress
+            3 => neighbor.chassis_id = as_mac(&val).unwrap_or_default(),
+            // mtxrNeighborVersion
+            4 => neighbor.system_description = as_string(&val).unwrap_or_default(),
+            // mtxrNeighborPlatform
+            5 => neighbor.platform = as_string(&val).unwrap_or_default(),
+            // mtxrNeighborIdentity
+            6 => neighbor.system_name = as_string(&val).unwrap_or_default(),
+            // mtxrNeighborInterfaceID (ifIndex)
+            8 => neighbor.local_port = as_int(&val)?,
+            _ => {}
+        }
+    }
+
+    Ok(rows.into_values().collect())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_neighbor_row() {
+        let column = |c: u32| format!("{MTXR_NEIGHBOR_TABLE}.{c}.7");
+        let walk = vec![
+            (
+                column(2),
+                ObjectValue::IpAddress("192.168.88.2".parse().unwrap()),
+            ),
+            (
+                column(3),
+                ObjectValue::String(vec![0x4c, 0x5e, 0x0c, 0x12, 0x34, 0x56]),
+            ),
+            (column(5), ObjectValue::String(b"MikroTik".to_vec())),
+            (column(6), ObjectValue::String(b"tower-3".to_vec())),
+            (column(8), ObjectValue::Integer(4)),
+            // A neighbor without an address
+            (
+                format!("{MTXR_NEIGHBOR_TABLE}.2.8"),
+                ObjectValue::IpAddress("0.0.0.0".parse().unwrap()),
+            ),
+        ];
+        let neighbors = parse(walk).unwrap();
+        assert_eq!(neighbors.len(), 2);
+        let neighbor = &neighbors[0];
+        assert_eq!(neighbor.protocol, NeighborProtocol::Mndp);
+        assert_eq!(neighbor.system_name, "tower-3");
+        assert_eq!(neighbor.chassis_id, "4C:5E:0C:12:34:56");
+        assert_eq!(neighbor.platform, "MikroTik");
+        assert_eq!(neighbor.local_port, 4);
+        assert_eq!(
+            neighbor.management_addresses,
+            vec!["192.168.88.2".parse::<std::net::IpAddr>().unwrap()]
+        );
+        assert!(neighbors[1].management_addresses.is_empty());
+    }
+}
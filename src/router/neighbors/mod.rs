@@ -0,0 +1,89 @@
+use anyhow::Result;
+use serde::Serialize;
+use std::net::IpAddr;
+mod cdp;
+mod lldp;
+mod mndp;
+
+pub(crate) use lldp::decode_address;
+
+/// The discovery protocol a neighbor was learned from.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+pub enum NeighborProtocol {
+    Lldp,
+    Cdp,
+    /// MikroTik Neighbor Discovery, read from MIKROTIK-MIB
+    Mndp,
+}
+
+impl NeighborProtocol {
+    pub fn name(&self) -> &'static str {
+        match self {
+            Self::Lldp => "LLDP",
+            Self::Cdp => "CDP",
+            Self::Mndp => "MNDP",
+        }
+    }
+}
+
+/// A device seen directly attached to one of the router's ports.
+#[derive(Debug, Clone, Serialize)]
+pub struct Neighbor {
+    pub protocol: NeighborProtocol,
+    /// The local port number the neighbor was seen on, as the protocol
+    /// numbers it (for LLDP-V2 this is the ifIndex)
+    pub local_port: i32,
+    /// The local port's name, if the protocol tells us
+    pub local_port_name: String,
+    pub chassis_id: String,
+    pub port_id: String,
+    pub port_description: String,
+    pub system_name: String,
+    pub system_description: String,
+    /// The neighbor's hardware platform, if the protocol reports it
+    pub platform: String,
+    pub management_addresses: Vec<IpAddr>,
+}
+
+impl Neighbor {
+    /// An empty neighbor entry, seen on `local_port`.
+    pub fn new(protocol: NeighborProtocol, local_port: i32) -> Self {
+        Self {
+            protocol,
+            local_port,
+            local_port_name: String::new(),
+            chassis_id: String::new(),
+            port_id: String::new(),
+            port_description: String::new(),
+            system_name: String::new(),
+            system_description: String::new(),
+            platform: String::new(),
+            management_addresses: Vec::new(),
+        }
+    }
+}
+
+#[derive(Debug, Default, Serialize)]
+pub struct Neighbors {
+    pub neighbors: Vec<Neighbor>,
+}
+
+impl Neighbors {
+    pub(crate) async fn from_snmp(ip_address: &str, community: &str) -> Result<Self> {
+        let (lldp, cdp, mndp) = tokio::join!(
+            lldp::from_snmp(ip_address, community),
+            cdp::from_snmp(ip_address, community),
+            mndp::from_snmp(ip_address, community),
+        );
+        if lldp.is_err() && cdp.is_err() && mndp.is_err() {
+            anyhow::bail!("No LLDP, CDP or MikroTik neighbor table on {ip_address}");
+        }
+        let neighbors = lldp
+            .unwrap_or_default()
+            .into_iter()
+            .chain(cdp.unwrap_or_default())
+            .chain(mndp.unwrap_or_default())
+            .collect();
+        Ok(Self { neighbors })
+    }
+}
@@ -0,0 +1,207 @@
+use super::Router;
+use serde::Serialize;
+use std::{collections::HashSet, net::IpAddr};
+
+/// A single recursive lookup performed while resolving a next hop.
+#[derive(Debug, Clone, Serialize)]
+pub struct ResolutionStep {
+    /// The address we were trying to reach at this step
+    pub address: IpAddr,
+    /// The route (destination/prefix) that matched it
+    pub route_destination: IpAddr,
+    pub route_netmask: u8,
+    /// The next hop that route points at
+    pub next_hop: IpAddr,
+}
+
+/// How a next-hop resolution ended.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+#[serde(tag = "type", content = "address")]
+pub enum ResolutionOutcome {
+    /// The address is inside one of the router's own connected subnets
+    Connected(IpAddr),
+    /// No (non-default) route covers the address
+    Unresolved(IpAddr),
+    /// The address was already visited; the routing table loops
+    Loop(IpAddr),
+    /// We gave up after the configured number of steps
+    DepthExceeded(IpAddr),
+}
+
+/// The result of recursively resolving a next hop, with every step
+/// recorded for diagnostics.
+#[derive(Debug, Clone, Serialize)]
+pub struct NextHopResolution {
+    pub start: IpAddr,
+    pub steps: Vec<ResolutionStep>,
+    pub outcome: ResolutionOutcome,
+}
+
+impl NextHopResolution {
+    /// The directly connected next hop, if resolution succeeded.
+    pub fn connected_next_hop(&self) -> Option<IpAddr> {
+        match self.outcome {
+            ResolutionOutcome::Connected(ip) => Some(ip),
+            _ => None,
+        }
+    }
+
+    /// Emit the resolution trace to the log.
+    pub fn log_trace(&self, hostname: &str) {
+        tracing::info!("{hostname}: resolving next hop {}", self.start);
+        for (depth, step) in self.steps.iter().enumerate() {
+            tracing::info!(
+                "{hostname}: [{depth}] {} via {}/{} -> {}",
+                step.address,
+                step.route_destination,
+                step.route_netmask,
+                step.next_hop
+            );
+        }
+        tracing::info!("{hostname}: resolution ended with {:?}", self.outcome);
+    }
+}
+
+impl Router {
+    /// Is `address` inside one of this router's own (connected) subnets?
+    pub fn is_connected(&self, address: IpAddr) -> bool {
+        self.ip_table
+            .ips
+            .iter()
+            .filter(|ip| !ip.address.is_unspecified())
+            .filter_map(|ip| ip.ip_network())
+            .any(|net| net.contains(address))
+    }
+
+    /// Does this router own `address`?
+    pub fn owns_address(&self, address: IpAddr) -> bool {
+        !address.is_unspecified() && self.ip_table.ips.iter().any(|ip| ip.address == address)
+    }
+
+    /// Recursively resolve `next_hop` through this router's routing table
+    /// until a directly connected next hop is found. Stops on loops, on
+    /// addresses only covered by a default route, or after `max_depth` steps.
+    pub fn resolve_next_hop(&self, next_hop: IpAddr, max_depth: usize) -> NextHopResolution {
+        let mut steps = Vec::new();
+        let mut visited = HashSet::new();
+        let mut current = next_hop;
+
+        let outcome = loop {
+            if self.is_connected(current) {
+                break ResolutionOutcome::Connected(current);
+            }
+            if !visited.insert(current) {
+                break ResolutionOutcome::Loop(current);
+            }
+            if steps.len() >= max_depth {
+                break ResolutionOutcome::DepthExceeded(current);
+            }
+            let Some(route) = self.ip_routes.longest_match(current) else {
+                break ResolutionOutcome::Unresolved(current);
+            };
+            if route.netmask == 0 {
+                // Recursing through the default route tells us nothing
+                break ResolutionOutcome::Unresolved(current);
+            }
+            steps.push(ResolutionStep {
+                address: current,
+                route_destination: route.destination,
+                route_netmask: route.netmask,
+                next_hop: route.next_hop,
+            });
+            if route.next_hop.is_unspecified() || self.owns_address(route.next_hop) {
+                // The route is directly attached (e.g. an interface route)
+                break ResolutionOutcome::Connected(current);
+            }
+            current = route.next_hop;
+        };
+
+        NextHopResolution {
+            start: next_hop,
+            steps,
+            outcome,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::router::fixtures::{route, router};
+
+    fn test_router(routes: Vec<crate::router::ip_routes::CidrEntry>) -> Router {
+        router("test", &["10.0.0.1/30"], routes)
+    }
+
+    #[test]
+    fn test_connected_next_hop() {
+        let router = test_router(vec![route("0.0.0.0", 0, "10.0.0.2")]);
+        let resolution = router.resolve_next_hop("10.0.0.2".parse().unwrap(), 8);
+        assert!(resolution.steps.is_empty());
+        assert_eq!(
+            resolution.connected_next_hop(),
+            Some("10.0.0.2".parse().unwrap())
+        );
+    }
+
+    #[test]
+    fn test_recursive_next_hop() {
+        let router = test_router(vec![
+            route("0.0.0.0", 0, "192.168.100.1"),
+            route("192.168.100.0", 24, "172.16.0.1"),
+            route("172.16.0.1", 32, "10.0.0.2"),
+        ]);
+        let resolution = router.resolve_next_hop("192.168.100.1".parse().unwrap(), 8);
+        assert_eq!(resolution.steps.len(), 2);
+        assert_eq!(
+            resolution.connected_next_hop(),
+            Some("10.0.0.2".parse().unwrap())
+        );
+    }
+
+    #[test]
+    fn test_next_hop_loop() {
+        let router = test_router(vec![
+            route("172.16.0.1", 32, "172.16.0.2"),
+            route("172.16.0.2", 32, "172.16.0.1"),
+        ]);
+        let resolution = router.resolve_next_hop("172.16.0.1".parse().unwrap(), 8);
+        assert_eq!(
+            resolution.outcome,
+            ResolutionOutcome::Loop("172.16.0.1".parse().unwrap())
+        );
+    }
+
+    #[test]
+    fn test_next_hop_depth_limit() {
+        let router = test_router(vec![
+            route("172.16.0.1", 32, "172.16.0.2"),
+            route("172.16.0.2", 32, "172.16.0.3"),
+            route("172.16.0.3", 32, "10.0.0.2"),
+        ]);
+        let resolution = router.resolve_next_hop("172.16.0.1".parse().unwrap(), 2);
+        assert_eq!(
+            resolution.outcome,
+            ResolutionOutcome::DepthExceeded("172.16.0.3".parse().unwrap())
+        );
+    }
+
+    #[test]
+    fn test_next_hop_only_default() {
+        let router = test_router(vec![route("0.0.0.0", 0, "172.16.0.1")]);
+        let resolution = router.resolve_next_hop("172.16.0.1".parse().unwrap(), 8);
+        assert_eq!(
+            resolution.outcome,
+            ResolutionOutcome::Unresolved("172.16.0.1".parse().unwrap())
+        );
+    }
+
+    #[test]
+    fn test_missing_netmask() {
+        // An address whose netmask never arrived, and a bogus 0.0.0.0 entry
+        let router = router("test", &["10.0.0.1/255", "0.0.0.0/0"], vec![]);
+        assert!(!router.is_connected("10.0.0.2".parse().unwrap()));
+        assert!(router.owns_address("10.0.0.1".parse().unwrap()));
+        assert!(!router.owns_address("0.0.0.0".parse().unwrap()));
+    }
+}
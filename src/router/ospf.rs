@@ -0,0 +1,260 @@
+use super::ip_table::IpTable;
+use crate::query_engine::{as_int, as_ip, oid_index, snmp_query};
+use anyhow::Result;
+use serde::Serialize;
+use std::{
+    collections::BTreeMap,
+    net::{IpAddr, Ipv4Addr},
+};
+
+// OSPF-MIB
+const OSPF_ROUTER_ID: &str = "1.3.6.1.2.1.14.1.1";
+const OSPF_AREA_TABLE: &str = "1.3.6.1.2.1.14.2.1";
+const OSPF_IF_TABLE: &str = "1.3.6.1.2.1.14.7.1";
+const OSPF_NBR_TABLE: &str = "1.3.6.1.2.1.14.10.1";
+
+/// ospfNbrState
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize)]
+pub enum OspfNeighborState {
+    #[default]
+    Down,
+    Attempt,
+    Init,
+    TwoWay,
+    ExchangeStart,
+    Exchange,
+    Loading,
+    Full,
+}
+
+impl From<i32> for OspfNeighborState {
+    fn from(value: i32) -> Self {
+        match value {
+            2 => Self::Attempt,
+            3 => Self::Init,
+            4 => Self::TwoWay,
+            5 => Self::ExchangeStart,
+            6 => Self::Exchange,
+            7 => Self::Loading,
+            8 => Self::Full,
+            _ => Self::Down,
+        }
+    }
+}
+
+/// ospfImportAsExtern: what kind of area this is
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize)]
+pub enum OspfAreaType {
+    #[default]
+    Normal,
+    Stub,
+    Nssa,
+}
+
+impl From<i32> for OspfAreaType {
+    fn from(value: i32) -> Self {
+        match value {
+            2 => Self::Stub,
+            3 => Self::Nssa,
+            _ => Self::Normal,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct OspfArea {
+    // .1 ospfAreaId
+    pub area_id: Ipv4Addr,
+    // .3 ospfImportAsExtern
+    pub area_type: OspfAreaType,
+    // .7 ospfAreaLsaCount
+    pub lsa_count: i32,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct OspfInterface {
+    // .1 ospfIfIpAddress (0.0.0.0 for unnumbered interfaces)
+    pub address: IpAddr,
+    // .2 ospfAddressLessIf (the ifIndex of unnumbered interfaces)
+    pub address_less_if: u32,
+    // .3 ospfIfAreaId
+    pub area_id: Ipv4Addr,
+    // .12 ospfIfState (1 = down ... 4 = point-to-point, 5 = DR, 6 = BDR)
+    pub state: i32,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct OspfNeighbor {
+    // .1 ospfNbrIpAddr
+    pub address: IpAddr,
+    // .3 ospfNbrRtrId
+    pub router_id: IpAddr,
+    // .6 ospfNbrState
+    pub state: OspfNeighborState,
+}
+
+#[derive(Debug, Default, Serialize)]
+pub struct Ospf {
+    pub router_id: Option<IpAddr>,
+    pub areas: Vec<OspfArea>,
+    pub interfaces: Vec<OspfInterface>,
+    pub neighbors: Vec<OspfNeighbor>,
+}
+
+/// Turn four index sub-identifiers into an address.
+fn ipv4_from_index(index: &[u32]) -> Option<Ipv4Addr> {
+    match index {
+        [a, b, c, d, ..] => Some(Ipv4Addr::new(*a as u8, *b as u8, *c as u8, *d as u8)),
+        _ => None,
+    }
+}
+
+impl Ospf {
+    /// Is this router running OSPF at all?
+    pub fn is_enabled(&self) -> bool {
+        !self.interfaces.is_empty() || !self.neighbors.is_empty()
+    }
+
+    /// The area `address` (a neighbor's address) is in, found from the
+    /// OSPF interface whose subnet holds it. `ip_table` supplies the masks.
+    pub fn area_of(&self, address: IpAddr, ip_table: &IpTable) -> Option<Ipv4Addr> {
+        self.interfaces
+            .iter()
+            .find(|iface| {
+                ip_table.ips.iter().any(|ip| {
+                    ip.address == iface.address
+                        && ip.ip_network().is_some_and(|net| net.contains(address))
+                })
+            })
+            .map(|iface| iface.area_id)
+    }
+
+    pub(crate) async fn from_snmp(ip_address: &str, community: &str) -> Result<Self> {
+        let mut result = Ospf::default();
+        for (oid, val) in snmp_query(ip_address, community, OSPF_ROUTER_ID).await? {
+            if oid == format!("{OSPF_ROUTER_ID}.0") {
+                // 0.0.0.0 when OSPF isn't running
+                result.router_id = Some(as_ip(&val)?).filter(|id| !id.is_unspecified());
+            }
+        }
+
+        let mut areas: BTreeMap<Ipv4Addr, OspfArea> = BTreeMap::new();
+        for (oid, val) in snmp_query(ip_address, community, OSPF_AREA_TABLE).await? {
+            // column.areaId
+            let Some(index) = oid_index(&oid, OSPF_AREA_TABLE) else {
+                continue;
+            };
+            let Some(area_id) = index.get(1..).and_then(ipv4_from_index) else {
+                continue;
+            };
+            let area = areas.entry(area_id).or_insert_with(|| OspfArea {
+                area_id,
+                area_type: OspfAreaType::Normal,
+                lsa_count: 0,
+            });
+            match index[0] {
+                3 => area.area_type = as_int(&val)?.into(),
+                7 => area.lsa_count = as_int(&val)?,
+                _ => {}
+            }
+        }
+        result.areas = areas.into_values().collect();
+
+        let mut interfaces: BTreeMap<(Ipv4Addr, u32), OspfInterface> = BTreeMap::new();
+        for (oid, val) in snmp_query(ip_address, community, OSPF_IF_TABLE).await? {
+            // column.ipAddress.addressLessIf
+            let Some(index) = oid_index(&oid, OSPF_IF_TABLE) else {
+                continue;
+            };
+            let (Some(address), Some(address_less_if)) =
+                (index.get(1..).and_then(ipv4_from_index), index.get(5))
+            else {
+                continue;
+            };
+            let iface = interfaces
+                .entry((address, *address_less_if))
+                .or_insert_with(|| OspfInterface {
+                    address: IpAddr::V4(address),
+                    address_less_if: *address_less_if,
+                    area_id: Ipv4Addr::UNSPECIFIED,
+                    state: 1,
+                });
+            match index[0] {
+                3 => {
+                    if let IpAddr::V4(area_id) = as_ip(&val)? {
+                        iface.area_id = area_id;
+                    }
+                }
+                12 => iface.state = as_int(&val)?,
+                _ => {}
+            }
+        }
+        result.interfaces = interfaces.into_values().collect();
+
+        let mut neighbors: BTreeMap<(Ipv4Addr, u32), OspfNeighbor> = BTreeMap::new();
+        for (oid, val) in snmp_query(ip_address, community, OSPF_NBR_TABLE).await? {
+            // column.nbrIpAddr.addressLessIndex
+            let Some(index) = oid_index(&oid, OSPF_NBR_TABLE) else {
+                continue;
+            };
+            let (Some(address), Some(address_less_index)) =
+                (index.get(1..).and_then(ipv4_from_index), index.get(5))
+            else {
+                continue;
+            };
+            let neighbor = neighbors
+                .entry((address, *address_less_index))
+                .or_insert_with(|| OspfNeighbor {
+                    address: IpAddr::V4(address),
+                    router_id: IpAddr::V4(Ipv4Addr::UNSPECIFIED),
+                    state: OspfNeighborState::Down,
+                });
+            match index[0] {
+                3 => neighbor.router_id = as_ip(&val)?,
+                6 => neighbor.state = as_int(&val)?.into(),
+                _ => {}
+            }
+        }
+        result.neighbors = neighbors.into_values().collect();
+
+        Ok(result)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::router::fixtures::router;
+
+    #[test]
+    fn test_area_of() {
+        let mut router = router("abr", &["10.0.0.1/30", "10.1.0.1/24"], vec![]);
+        router.ospf.interfaces = vec![
+            OspfInterface {
+                address: "10.0.0.1".parse().unwrap(),
+                address_less_if: 0,
+                area_id: Ipv4Addr::UNSPECIFIED,
+                state: 4,
+            },
+            OspfInterface {
+                address: "10.1.0.1".parse().unwrap(),
+                address_less_if: 0,
+                area_id: Ipv4Addr::new(0, 0, 0, 10),
+                state: 5,
+            },
+        ];
+        let ospf = &router.ospf;
+        assert_eq!(
+            ospf.area_of("10.1.0.7".parse().unwrap(), &router.ip_table),
+            Some(Ipv4Addr::new(0, 0, 0, 10))
+        );
+        assert_eq!(
+            ospf.area_of("10.0.0.2".parse().unwrap(), &router.ip_table),
+            Some(Ipv4Addr::UNSPECIFIED)
+        );
+        assert_eq!(
+            ospf.area_of("192.0.2.1".parse().unwrap(), &router.ip_table),
+            None
+        );
+    }
+}
@@ -0,0 +1,253 @@
+use super::{Table, VendorProfile};
+use crate::router::{system_info::SystemInfo, Fingerprint, Vendor};
+use std::time::Duration;
+
+/// Anything we don't have a profile for: walk everything, carefully.
+pub(super) struct Generic;
+
+impl VendorProfile for Generic {
+    fn name(&self) -> &str {
+        "Generic"
+    }
+
+    fn matches(&self, _: &SystemInfo, _: &Fingerprint) -> bool {
+        true
+    }
+}
+
+/// RouterOS drops responses to large GetBulk requests, so it stays at the
+/// conservative default. It has no ENTITY-MIB.
+pub(super) struct MikroTik;
+
+impl VendorProfile for MikroTik {
+    fn name(&self) -> &str {
+        "MikroTik RouterOS"
+    }
+
+    fn matches(&self, _: &SystemInfo, fingerprint: &Fingerprint) -> bool {
+        fingerprint.vendor == Vendor::MikroTik
+    }
+
+    fn walks(&self, table: Table) -> bool {
+        table != Table::Inventory
+    }
+
+    fn extra_tables(&self) -> Vec<(String, String)> {
+        // mtxrLicense: software ID, licence level and upgrade-until date
+        vec![(
+            "RouterOS licence".to_string(),
+            "1.3.6.1.4.1.14988.1.1.4".to_string(),
+        )]
+    }
+}
+
+/// IOS and IOS XE handle big bulk requests, but their SNMP process runs at
+/// low priority, so large tables on a busy box need a longer timeout.
+pub(super) struct CiscoIos;
+
+impl VendorProfile for CiscoIos {
+    fn name(&self) -> &str {
+        "Cisco IOS/IOS XE"
+    }
+
+    fn matches(&self, _: &SystemInfo, fingerprint: &Fingerprint) -> bool {
+        fingerprint.vendor == Vendor::Cisco && matches!(fingerprint.os.as_str(), "IOS" | "IOS XE")
+    }
+
+    fn max_repetitions(&self) -> u32 {
+        50
+    }
+
+    fn timeout(&self) -> Duration {
+        Duration::from_secs(10)
+    }
+
+    fn walks(&self, table: Table) -> bool {
+        table != Table::Wireless
+    }
+}
+
+/// NX-OS answers big bulk requests quickly.
+pub(super) struct CiscoNxOs;
+
+impl VendorProfile for CiscoNxOs {
+    fn name(&self) -> &str {
+        "Cisco NX-OS"
+    }
+
+    fn matches(&self, _: &SystemInfo, fingerprint: &Fingerprint) -> bool {
+        fingerprint.vendor == Vendor::Cisco && fingerprint.os == "NX-OS"
+    }
+
+    fn max_repetitions(&self) -> u32 {
+        50
+    }
+
+    fn walks(&self, table: Table) -> bool {
+        table != Table::Wireless
+    }
+}
+
+pub(super) struct Juniper;
+
+impl VendorProfile for Juniper {
+    fn name(&self) -> &str {
+        "Juniper Junos"
+    }
+
+    fn matches(&self, _: &SystemInfo, fingerprint: &Fingerprint) -> bool {
+        fingerprint.vendor == Vendor::Juniper
+    }
+
+    fn max_repetitions(&self) -> u32 {
+        50
+    }
+
+    fn timeout(&self) -> Duration {
+        Duration::from_secs(10)
+    }
+
+    fn walks(&self, table: Table) -> bool {
+        table != Table::Wireless
+    }
+}
+
+pub(super) struct Arista;
+
+impl VendorProfile for Arista {
+    fn name(&self) -> &str {
+        "Arista EOS"
+    }
+
+    fn matches(&self, _: &SystemInfo, fingerprint: &Fingerprint) -> bool {
+        fingerprint.vendor == Vendor::Arista
+    }
+
+    fn max_repetitions(&self) -> u32 {
+        50
+    }
+
+    fn walks(&self, table: Table) -> bool {
+        table != Table::Wireless
+    }
+}
+
+pub(super) struct Huawei;
+
+impl VendorProfile for Huawei {
+    fn name(&self) -> &str {
+        "Huawei VRP"
+    }
+
+    fn matches(&self, _: &SystemInfo, fingerprint: &Fingerprint) -> bool {
+        fingerprint.vendor == Vendor::Huawei
+    }
+
+    fn max_repetitions(&self) -> u32 {
+        50
+    }
+
+    fn walks(&self, table: Table) -> bool {
+        table != Table::Wireless
+    }
+}
+
+/// EdgeOS is Vyatta on net-snmp: no ENTITY-MIB, no radios, and its routing
+/// daemons don't export BGP4-MIB or OSPF-MIB.
+pub(super) struct EdgeOs;
+
+impl VendorProfile for EdgeOs {
+    fn name(&self) -> &str {
+        "Ubiquiti EdgeOS"
+    }
+
+    fn matches(&self, _: &SystemInfo, fingerprint: &Fingerprint) -> bool {
+        fingerprint.vendor == Vendor::Ubiquiti && fingerprint.os == "EdgeOS"
+    }
+
+    fn max_repetitions(&self) -> u32 {
+        40
+    }
+
+    fn walks(&self, table: Table) -> bool {
+        !matches!(
+            table,
+            Table::Inventory | Table::Wireless | Table::Bgp | Table::Ospf | Table::Vrfs
+        )
+    }
+}
+
+/// A Linux (or BSD) box running net-snmp. BGP and OSPF are left enabled, as
+/// FRR and BIRD can export them over AgentX.
+pub(super) struct NetSnmp;
+
+impl VendorProfile for NetSnmp {
+    fn name(&self) -> &str {
+        "Linux net-snmp"
+    }
+
+    fn matches(&self, _: &SystemInfo, fingerprint: &Fingerprint) -> bool {
+        fingerprint.vendor == Vendor::NetSnmp
+    }
+
+    fn max_repetitions(&self) -> u32 {
+        40
+    }
+
+    fn walks(&self, table: Table) -> bool {
+        !matches!(table, Table::Inventory | Table::Wireless | Table::Vrfs)
+    }
+}
+
+/// The first built-in profile matching the device.
+pub(super) fn select(
+    system_info: &SystemInfo,
+    fingerprint: &Fingerprint,
+) -> Box<dyn VendorProfile> {
+    let profiles: Vec<Box<dyn VendorProfile>> = vec![
+        Box::new(MikroTik),
+        Box::new(CiscoIos),
+        Box::new(CiscoNxOs),
+        Box::new(Juniper),
+        Box::new(Arista),
+        Box::new(Huawei),
+        Box::new(EdgeOs),
+        Box::new(NetSnmp),
+    ];
+    profiles
+        .into_iter()
+        .find(|p| p.matches(system_info, fingerprint))
+        .unwrap_or_else(|| Box::new(Generic))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn select_for(object_id: &str, descr: &str) -> Box<dyn VendorProfile> {
+        let system_info = SystemInfo {
+            object_id: object_id.to_string(),
+            platform: descr.to_string(),
+            ..Default::default()
+        };
+        select(&system_info, &Fingerprint::identify(&system_info))
+    }
+
+    #[test]
+    fn test_bulk_sizes() {
+        let nx_os = select_for(
+            "1.3.6.1.4.1.9.12.3.1.3.1084",
+            "Cisco NX-OS(tm) n9000, Software (n9000-dk9), Version 9.3(8), RELEASE SOFTWARE",
+        );
+        assert_eq!(nx_os.name(), "Cisco NX-OS");
+        assert_eq!(nx_os.max_repetitions(), 50);
+        let arista = select_for("1.3.6.1.4.1.30065.1.3011.7280.3", "Arista Networks EOS");
+        assert_eq!(arista.max_repetitions(), 50);
+        let huawei = select_for(
+            "1.3.6.1.4.1.2011.2.224.279",
+            "Huawei Versatile Routing Platform",
+        );
+        assert_eq!(huawei.max_repetitions(), 50);
+        assert_eq!(select_for("1.3.6.1.4.1.99", "").name(), "Generic");
+    }
+}
@@ -0,0 +1,110 @@
+//! Per-vendor collector profiles: which tables to walk on a device and how
+//! to query it, chosen once the device has been fingerprinted.
+use super::{system_info::SystemInfo, Fingerprint};
+use crate::config::CONFIG;
+use crate::query_engine::{as_string, snmp_query, QuerySettings, MAX_REPEAT, TIMEOUT};
+use serde::Deserialize;
+use std::{collections::BTreeMap, time::Duration};
+mod builtin;
+mod user;
+pub use user::ProfileConfig;
+
+/// The optional tables a profile can switch off. The system group, address
+/// table and routing table are always walked.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Deserialize)]
+pub enum Table {
+    Interfaces,
+    Neighbors,
+    Arp,
+    Bridge,
+    Bgp,
+    Ospf,
+    Inventory,
+    Health,
+    Vrfs,
+    Wireless,
+}
+
+/// How to collect from one family of devices. Every method has a default
+/// that suits a standards-compliant agent, so profiles only override what
+/// their devices get wrong.
+pub trait VendorProfile: Send + Sync {
+    fn name(&self) -> &str;
+
+    /// Does this profile describe the device?
+    fn matches(&self, system_info: &SystemInfo, fingerprint: &Fingerprint) -> bool;
+
+    /// Rows to ask for per GetBulk request
+    fn max_repetitions(&self) -> u32 {
+        MAX_REPEAT
+    }
+
+    fn timeout(&self) -> Duration {
+        TIMEOUT
+    }
+
+    /// Should `table` be walked on this device?
+    fn walks(&self, _table: Table) -> bool {
+        true
+    }
+
+    /// Tables the device keeps somewhere other than the standard OID, as
+    /// (standard prefix, actual prefix)
+    fn oid_fixups(&self) -> Vec<(String, String)> {
+        Vec::new()
+    }
+
+    /// Skip ipCidrRouteTable and read the RFC1213 ipRouteTable directly
+    fn old_route_table(&self) -> bool {
+        false
+    }
+
+    /// Extra vendor tables to walk and keep as-is, as (name, OID)
+    fn extra_tables(&self) -> Vec<(String, String)> {
+        Vec::new()
+    }
+
+    fn query_settings(&self) -> QuerySettings {
+        QuerySettings {
+            max_repetitions: self.max_repetitions(),
+            timeout: self.timeout(),
+            oid_fixups: self.oid_fixups(),
+        }
+    }
+}
+
+/// Walk each extra table, keeping every value as text.
+pub(crate) async fn walk_extra_tables(
+    ip_address: &str,
+    community: &str,
+    tables: Vec<(String, String)>,
+) -> BTreeMap<String, Vec<(String, String)>> {
+    let mut result = BTreeMap::new();
+    for (name, oid) in tables {
+        match snmp_query(ip_address, community, &oid).await {
+            Ok(values) => {
+                let values = values
+                    .into_iter()
+                    .map(|(oid, val)| (oid, as_string(&val).unwrap_or_else(|_| format!("{val:?}"))))
+                    .collect();
+                result.insert(name, values);
+            }
+            Err(e) => tracing::warn!("Unable to query {ip_address} for {name}: {e}"),
+        }
+    }
+    result
+}
+
+/// Pick the profile for a device: the first matching user-defined profile
+/// (layered over the built-in one), otherwise the first matching built-in.
+pub fn select(system_info: &SystemInfo, fingerprint: &Fingerprint) -> Box<dyn VendorProfile> {
+    let base = builtin::select(system_info, fingerprint);
+    match CONFIG
+        .profiles
+        .iter()
+        .find(|p| p.matches(system_info, fingerprint))
+    {
+        Some(config) => Box::new(user::UserProfile::new(config.clone(), base)),
+        None => base,
+    }
+}
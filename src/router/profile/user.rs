@@ -0,0 +1,182 @@
+use super::{Table, VendorProfile};
+use crate::router::{system_info::SystemInfo, Fingerprint, Vendor};
+use regex::Regex;
+use serde::Deserialize;
+use std::time::Duration;
+
+/// A table relocated on the device: walk `actual` whenever `standard` is
+/// asked for.
+#[derive(Debug, Clone, Deserialize)]
+pub struct OidFixup {
+    pub standard: String,
+    pub actual: String,
+}
+
+/// An extra table to walk and keep.
+#[derive(Debug, Clone, Deserialize)]
+pub struct ExtraTable {
+    pub name: String,
+    pub oid: String,
+}
+
+/// A profile defined in `router_mapper.toml`. The match fields are ANDed
+/// together; anything not set falls through to the built-in profile the
+/// device would otherwise get.
+#[derive(Debug, Clone, Default, Deserialize)]
+#[serde(default)]
+pub struct ProfileConfig {
+    pub name: String,
+    pub vendor: Option<Vendor>,
+    /// sysObjectID starts with this
+    pub object_id_prefix: Option<String>,
+    /// sysDescr matches this regular expression
+    pub descr_regex: Option<String>,
+    pub max_repetitions: Option<u32>,
+    pub timeout_seconds: Option<u64>,
+    pub skip_tables: Vec<Table>,
+    pub oid_fixups: Vec<OidFixup>,
+    pub old_route_table: Option<bool>,
+    pub extra_tables: Vec<ExtraTable>,
+}
+
+impl ProfileConfig {
+    pub fn matches(&self, system_info: &SystemInfo, fingerprint: &Fingerprint) -> bool {
+        let vendor = self.vendor.is_none_or(|v| v == fingerprint.vendor);
+        let object_id = self.object_id_prefix.as_ref().is_none_or(|prefix| {
+            system_info.object_id == *prefix
+                || system_info.object_id.starts_with(&format!("{prefix}."))
+        });
+        let descr = self
+            .descr_regex
+            .as_ref()
+            .is_none_or(|pattern| match Regex::new(pattern) {
+                Ok(regex) => regex.is_match(&system_info.platform),
+                Err(e) => {
+                    tracing::error!("Profile {}: bad descr_regex: {e}", self.name);
+                    false
+                }
+            });
+        vendor && object_id && descr
+    }
+}
+
+/// A configured profile layered over a built-in one.
+pub(super) struct UserProfile {
+    config: ProfileConfig,
+    base: Box<dyn VendorProfile>,
+}
+
+impl UserProfile {
+    pub(super) fn new(config: ProfileConfig, base: Box<dyn VendorProfile>) -> Self {
+        Self { config, base }
+    }
+}
+
+impl VendorProfile for UserProfile {
+    fn name(&self) -> &str {
+        &self.config.name
+    }
+
+    fn matches(&self, system_info: &SystemInfo, fingerprint: &Fingerprint) -> bool {
+        self.config.matches(system_info, fingerprint)
+    }
+
+    fn max_repetitions(&self) -> u32 {
+        self.config
+            .max_repetitions
+            .unwrap_or_else(|| self.base.max_repetitions())
+    }
+
+    fn timeout(&self) -> Duration {
+        self.config
+            .timeout_seconds
+            .map(Duration::from_secs)
+            .unwrap_or_else(|| self.base.timeout())
+    }
+
+    fn walks(&self, table: Table) -> bool {
+        !self.config.skip_tables.contains(&table) && self.base.walks(table)
+    }
+
+    fn oid_fixups(&self) -> Vec<(String, String)> {
+        self.config
+            .oid_fixups
+            .iter()
+            .map(|f| (f.standard.clone(), f.actual.clone()))
+            .chain(self.base.oid_fixups())
+            .collect()
+    }
+
+    fn old_route_table(&self) -> bool {
+        self.config
+            .old_route_table
+            .unwrap_or_else(|| self.base.old_route_table())
+    }
+
+    fn extra_tables(&self) -> Vec<(String, String)> {
+        self.base
+            .extra_tables()
+            .into_iter()
+            .chain(
+                self.config
+                    .extra_tables
+                    .iter()
+                    .map(|t| (t.name.clone(), t.oid.clone())),
+            )
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::super::builtin;
+    use super::*;
+
+    #[test]
+    fn test_user_profile_layers_over_builtin() {
+        let system_info = SystemInfo {
+            object_id: "1.3.6.1.4.1.14988.1".to_string(),
+            platform: "RouterOS CCR1036-8G-2S+".to_string(),
+            ..Default::default()
+        };
+        let fingerprint = Fingerprint::identify(&system_info);
+        let base = builtin::select(&system_info, &fingerprint);
+        assert_eq!(base.name(), "MikroTik RouterOS");
+
+        let config = ProfileConfig {
+            name: "Old CCRs".to_string(),
+            object_id_prefix: Some("1.3.6.1.4.1.14988".to_string()),
+            descr_regex: Some("^RouterOS CCR".to_string()),
+            timeout_seconds: Some(15),
+            skip_tables: vec![Table::Bgp],
+            ..Default::default()
+        };
+        assert!(config.matches(&system_info, &fingerprint));
+        let profile = UserProfile::new(config, base);
+        assert_eq!(profile.timeout(), Duration::from_secs(15));
+        // Not overridden, so still the built-in MikroTik value
+        assert_eq!(profile.max_repetitions(), 20);
+        assert!(!profile.walks(Table::Bgp));
+        assert!(!profile.walks(Table::Inventory));
+        assert!(profile.walks(Table::Ospf));
+        assert_eq!(profile.extra_tables().len(), 1);
+    }
+
+    #[test]
+    fn test_profile_match_fields() {
+        let system_info = SystemInfo {
+            object_id: "1.3.6.1.4.1.99".to_string(),
+            ..Default::default()
+        };
+        let fingerprint = Fingerprint::identify(&system_info);
+        let mut config = ProfileConfig {
+            object_id_prefix: Some("1.3.6.1.4.1.9".to_string()),
+            ..Default::default()
+        };
+        // 1.3.6.1.4.1.99 isn't under 1.3.6.1.4.1.9
+        assert!(!config.matches(&system_info, &fingerprint));
+        config.object_id_prefix = None;
+        config.vendor = Some(Vendor::Unknown);
+        assert!(config.matches(&system_info, &fingerprint));
+    }
+}
@@ -1,12 +1,18 @@
-use crate::query_engine::{as_string, snmp_query};
+use crate::query_engine::{as_int, as_oid, as_string, as_u32, snmp_query};
 use anyhow::Result;
+use serde::Serialize;
 
-#[derive(Default, Debug)]
+#[derive(Default, Debug, Serialize)]
 pub struct SystemInfo {
     pub platform: String,
+    pub object_id: String,
+    /// sysUpTime, in hundredths of a second
+    pub uptime_ticks: u32,
     pub contact: String,
     pub hostname: String,
     pub location: String,
+    /// sysServices bitmask (2 = datalink, 4 = internet, 8 = end-to-end, ...)
+    pub services: i32,
 }
 
 impl SystemInfo {
@@ -14,15 +20,40 @@ impl SystemInfo {
         let info = snmp_query(ip, community, "1.3.6.1.2.1.1").await?;
         let mut result = SystemInfo::default();
         for (oid, val) in info {
+            // Only used for fingerprinting, so a device that returns the
+            // wrong type for these is still usable
             match oid.as_str() {
                 "1.3.6.1.2.1.1.1.0" => result.platform = as_string(&val)?,
+                "1.3.6.1.2.1.1.2.0" => result.object_id = as_oid(&val).unwrap_or_default(),
+                "1.3.6.1.2.1.1.3.0" => result.uptime_ticks = as_u32(&val).unwrap_or_default(),
                 "1.3.6.1.2.1.1.4.0" => result.contact = as_string(&val)?,
                 "1.3.6.1.2.1.1.5.0" => result.hostname = as_string(&val)?,
                 "1.3.6.1.2.1.1.6.0" => result.location = as_string(&val)?,
+                "1.3.6.1.2.1.1.7.0" => result.services = as_int(&val).unwrap_or_default(),
                 _ => {}
             }
         }
 
         Ok(result)
     }
+
+    /// Uptime in whole seconds.
+    pub fn uptime_seconds(&self) -> u64 {
+        self.uptime_ticks as u64 / 100
+    }
+
+    /// Does sysServices say this device routes (layer 3)?
+    pub fn is_router(&self) -> bool {
+        self.services & 0x04 != 0
+    }
+
+    /// The IANA enterprise number from sysObjectID (1.3.6.1.4.1.<n>...).
+    pub fn enterprise_number(&self) -> Option<u32> {
+        self.object_id
+            .strip_prefix("1.3.6.1.4.1.")?
+            .split('.')
+            .next()?
+            .parse()
+            .ok()
+    }
 }
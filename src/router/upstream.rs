@@ -0,0 +1,150 @@
+use super::Router;
+use crate::router::ip_routes::CidrEntry;
+use ipnetwork::IpNetwork;
+use serde::Serialize;
+use std::net::IpAddr;
+
+/// Which heuristic selected a router's upstream route.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+#[serde(tag = "type", content = "value")]
+pub enum UpstreamSource {
+    /// The router's default (0.0.0.0/0 or ::/0) route
+    DefaultRoute,
+    /// The route covering a configured "upstream" prefix
+    UpstreamPrefix(IpNetwork),
+    /// The route the router uses to reach the management seed address
+    ManagementSeed(IpAddr),
+}
+
+/// The route a router appears to use to reach the rest of the network.
+#[derive(Debug, Clone, Serialize)]
+pub struct UpstreamRoute {
+    pub source: UpstreamSource,
+    pub destination: IpAddr,
+    pub netmask: u8,
+    pub next_hop: IpAddr,
+}
+
+impl UpstreamRoute {
+    fn new(source: UpstreamSource, route: &CidrEntry) -> Self {
+        Self {
+            source,
+            destination: route.destination,
+            netmask: route.netmask,
+            next_hop: route.next_hop,
+        }
+    }
+}
+
+impl Router {
+    /// Pick the route pointing towards this router's parent. The default
+    /// route is preferred; routers without one (border routers carrying full
+    /// tables, route reflectors) fall back to the route covering one of the
+    /// configured upstream prefixes, then to the route towards the management
+    /// seed address. Returns `None` if nothing usable is found, in which case
+    /// the router should be treated as a root.
+    pub fn upstream_route(
+        &self,
+        upstream_prefixes: &[IpNetwork],
+        management_seed: Option<IpAddr>,
+    ) -> Option<UpstreamRoute> {
+        if let Some(route) = self
+            .ip_routes
+            .routes
+            .iter()
+            .find(|r| r.netmask == 0 && !r.next_hop.is_unspecified())
+        {
+            return Some(UpstreamRoute::new(UpstreamSource::DefaultRoute, route));
+        }
+
+        for prefix in upstream_prefixes {
+            if let Some(route) = self.specific_route_to(prefix.ip()) {
+                return Some(UpstreamRoute::new(
+                    UpstreamSource::UpstreamPrefix(*prefix),
+                    route,
+                ));
+            }
+        }
+
+        if let Some(seed) = management_seed {
+            if let Some(route) = self.specific_route_to(seed) {
+                return Some(UpstreamRoute::new(
+                    UpstreamSource::ManagementSeed(seed),
+                    route,
+                ));
+            }
+        }
+
+        None
+    }
+
+    /// The most specific non-default route to `address` that goes via
+    /// another router.
+    fn specific_route_to(&self, address: IpAddr) -> Option<&CidrEntry> {
+        self.ip_routes
+            .longest_match(address)
+            .filter(|r| r.netmask != 0 && self.is_usable_next_hop(r.next_hop))
+    }
+
+    fn is_usable_next_hop(&self, next_hop: IpAddr) -> bool {
+        !next_hop.is_unspecified() && !self.owns_address(next_hop)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::router::fixtures::{route, router};
+
+    #[test]
+    fn test_default_route_preferred() {
+        let router = router(
+            "edge",
+            &["10.0.0.1/30"],
+            vec![
+                route("0.0.0.0", 0, "10.0.0.2"),
+                route("192.168.0.0", 16, "10.0.0.2"),
+            ],
+        );
+        let upstream = router.upstream_route(&[], None).unwrap();
+        assert_eq!(upstream.source, UpstreamSource::DefaultRoute);
+    }
+
+    #[test]
+    fn test_upstream_prefix_without_default() {
+        let prefix: IpNetwork = "100.64.0.0/10".parse().unwrap();
+        let router = router(
+            "border",
+            &["10.0.0.1/30"],
+            vec![
+                route("10.0.0.0", 30, "0.0.0.0"),
+                route("100.64.0.0", 10, "10.0.0.2"),
+            ],
+        );
+        let upstream = router.upstream_route(&[prefix], None).unwrap();
+        assert_eq!(upstream.source, UpstreamSource::UpstreamPrefix(prefix));
+        assert_eq!(upstream.next_hop, "10.0.0.2".parse::<IpAddr>().unwrap());
+    }
+
+    #[test]
+    fn test_management_seed_without_default() {
+        let seed: IpAddr = "172.16.5.10".parse().unwrap();
+        let router = router(
+            "reflector",
+            &["10.0.0.1/30"],
+            vec![route("172.16.0.0", 16, "10.0.0.2")],
+        );
+        let upstream = router.upstream_route(&[], Some(seed)).unwrap();
+        assert_eq!(upstream.source, UpstreamSource::ManagementSeed(seed));
+    }
+
+    #[test]
+    fn test_no_upstream_is_root() {
+        let router = router(
+            "isolated",
+            &["10.0.0.1/30"],
+            vec![route("10.0.0.0", 30, "0.0.0.0")],
+        );
+        assert!(router.upstream_route(&[], None).is_none());
+    }
+}
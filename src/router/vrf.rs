@@ -0,0 +1,232 @@
+use super::ip_routes::{CidrEntry, IpRoutes};
+use crate::query_engine::{as_int, as_string, oid_index, snmp_query};
+use anyhow::Result;
+use serde::Serialize;
+use std::{
+    collections::BTreeMap,
+    net::{IpAddr, Ipv4Addr, Ipv6Addr},
+    sync::Arc,
+};
+use tokio::{sync::Semaphore, task::JoinSet};
+use tracing::{debug, warn};
+
+// MPLS-L3VPN-STD-MIB
+const VRF_TABLE: &str = "1.3.6.1.2.1.10.166.11.1.2.2.1";
+// mplsL3VpnIfVpnClassification, indexed by vrfName.ifIndex
+const IF_VPN_CLASSIFICATION: &str = "1.3.6.1.2.1.10.166.11.1.2.1.1.2";
+// mplsL3VpnVrfRteInetCidrIfIndex; everything else we need is in the index
+const VRF_RTE_IF_INDEX: &str = "1.3.6.1.2.1.10.166.11.1.4.1.1.7";
+
+// Routing tables can be large; don't walk too many at once on one device
+const MAX_CONCURRENT_VRF_WALKS: usize = 4;
+
+/// A VPN routing and forwarding instance.
+#[derive(Debug, Clone, Default, Serialize)]
+pub struct Vrf {
+    pub name: String,
+    // mplsL3VpnVrfDescription
+    pub description: String,
+    // mplsL3VpnVrfRD
+    pub route_distinguisher: String,
+    /// ifIndexes bound to this VRF
+    pub interfaces: Vec<i32>,
+    pub routes: IpRoutes,
+}
+
+/// The VRFs configured on a router (other than the global table).
+#[derive(Debug, Default, Serialize)]
+pub struct Vrfs {
+    pub vrfs: Vec<Vrf>,
+}
+
+/// Split a length-prefixed octet string off the front of an index.
+fn take_octets(index: &[u32]) -> Option<(Vec<u8>, &[u32])> {
+    let (length, rest) = index.split_first()?;
+    let length = *length as usize;
+    if rest.len() < length {
+        return None;
+    }
+    let bytes = rest[..length].iter().map(|b| *b as u8).collect();
+    Some((bytes, &rest[length..]))
+}
+
+fn take_name(index: &[u32]) -> Option<(String, &[u32])> {
+    let (bytes, rest) = take_octets(index)?;
+    Some((String::from_utf8(bytes).ok()?, rest))
+}
+
+fn to_address(address_type: u32, bytes: &[u8]) -> Option<IpAddr> {
+    match (address_type, bytes.len()) {
+        (1, 4) => Some(IpAddr::V4(Ipv4Addr::new(
+            bytes[0], bytes[1], bytes[2], bytes[3],
+        ))),
+        (2, 16) => {
+            let octets: [u8; 16] = bytes.try_into().ok()?;
+            Some(IpAddr::V6(Ipv6Addr::from(octets)))
+        }
+        _ => None,
+    }
+}
+
+/// Decode an mplsL3VpnVrfRteTable index:
+/// `vrfName, destType, dest, pfxLen, policy, nextHopType, nextHop`
+fn route_from_index(index: &[u32], if_index: i32) -> Option<(String, CidrEntry)> {
+    let (vrf, rest) = take_name(index)?;
+    let (dest_type, rest) = rest.split_first()?;
+    let (dest, rest) = take_octets(rest)?;
+    let (netmask, rest) = rest.split_first()?;
+    // The policy is an OBJECT IDENTIFIER, also length-prefixed
+    let (_, rest) = take_octets(rest)?;
+    let (next_hop_type, rest) = rest.split_first()?;
+    let (next_hop, _) = take_octets(rest)?;
+    let destination = to_address(*dest_type, &dest)?;
+    let next_hop = to_address(*next_hop_type, &next_hop).unwrap_or(match destination {
+        IpAddr::V4(_) => IpAddr::V4(Ipv4Addr::UNSPECIFIED),
+        IpAddr::V6(_) => IpAddr::V6(Ipv6Addr::UNSPECIFIED),
+    });
+    Some((
+        vrf,
+        CidrEntry {
+            destination,
+            netmask: *netmask as u8,
+            next_hop,
+            if_index,
+        },
+    ))
+}
+
+impl Vrfs {
+    pub fn get(&self, name: &str) -> Option<&Vrf> {
+        self.vrfs.iter().find(|v| v.name == name)
+    }
+
+    /// Discover VRFs and load each one's routing table. `wanted` is always
+    /// included, even if the device doesn't implement MPLS-L3VPN-STD-MIB, so
+    /// its routes can be read through a context-indexed community.
+    ///
+    /// Only the "community@vrf" convention is supported; SNMPv3 contexts
+    /// aren't, as the SNMP client only speaks v2c.
+    pub(crate) async fn from_snmp(
+        ip_address: &str,
+        community: &str,
+        wanted: Option<&str>,
+    ) -> Result<Self> {
+        let mut vrfs = match Self::discover(ip_address, community).await {
+            Ok(vrfs) => vrfs,
+            Err(e) => {
+                debug!("No MPLS-L3VPN-STD-MIB VRFs on {ip_address}: {e}");
+                BTreeMap::new()
+            }
+        };
+        if let Some(name) = wanted {
+            vrfs.entry(name.to_string()).or_insert_with(|| Vrf {
+                name: name.to_string(),
+                ..Default::default()
+            });
+        }
+
+        // Agents without mplsL3VpnVrfRteTable usually expose each VRF's
+        // global tables under a "community@vrf" context instead
+        let permits = Arc::new(Semaphore::new(MAX_CONCURRENT_VRF_WALKS));
+        let mut set = JoinSet::new();
+        for vrf in vrfs.values().filter(|v| v.routes.routes.is_empty()) {
+            let name = vrf.name.clone();
+            let ip = ip_address.to_string();
+            let context = format!("{community}@{name}");
+            let permits = permits.clone();
+            set.spawn(async move {
+                let _permit = permits.acquire_owned().await;
+                (name, IpRoutes::from_snmp(&ip, &context).await)
+            });
+        }
+        while let Some(res) = set.join_next().await {
+            match res {
+                Ok((name, Ok(routes))) => {
+                    if let Some(vrf) = vrfs.get_mut(&name) {
+                        vrf.routes = routes;
+                    }
+                }
+                Ok((name, Err(e))) => {
+                    warn!("Unable to read routes for VRF {name} on {ip_address}: {e}")
+                }
+                Err(e) => warn!("JoinSet Error: {:?}", e),
+            }
+        }
+
+        Ok(Self {
+            vrfs: vrfs.into_values().collect(),
+        })
+    }
+
+    async fn discover(ip_address: &str, community: &str) -> Result<BTreeMap<String, Vrf>> {
+        let mut vrfs: BTreeMap<String, Vrf> = BTreeMap::new();
+        for (oid, val) in snmp_query(ip_address, community, VRF_TABLE).await? {
+            let Some(index) = oid_index(&oid, VRF_TABLE) else {
+                continue;
+            };
+            let Some((column, rest)) = index.split_first() else {
+                continue;
+            };
+            let Some((name, _)) = take_name(rest) else {
+                continue;
+            };
+            let vrf = vrfs.entry(name.clone()).or_insert_with(|| Vrf {
+                name,
+                ..Default::default()
+            });
+            match column {
+                3 => vrf.description = as_string(&val).unwrap_or_default(),
+                4 => vrf.route_distinguisher = as_string(&val).unwrap_or_default(),
+                _ => {}
+            }
+        }
+
+        for (oid, _) in snmp_query(ip_address, community, IF_VPN_CLASSIFICATION).await? {
+            let Some(index) = oid_index(&oid, IF_VPN_CLASSIFICATION) else {
+                continue;
+            };
+            let Some((name, [if_index])) = take_name(&index) else {
+                continue;
+            };
+            if let Some(vrf) = vrfs.get_mut(&name) {
+                vrf.interfaces.push(*if_index as i32);
+            }
+        }
+
+        for (oid, val) in snmp_query(ip_address, community, VRF_RTE_IF_INDEX).await? {
+            let Some(index) = oid_index(&oid, VRF_RTE_IF_INDEX) else {
+                continue;
+            };
+            let Some((name, route)) = route_from_index(&index, as_int(&val)?) else {
+                continue;
+            };
+            if let Some(vrf) = vrfs.get_mut(&name) {
+                vrf.routes.routes.push(route);
+            }
+        }
+        Ok(vrfs)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_route_from_index() {
+        // VRF "cust", 10.1.0.0/16 via 192.168.0.1
+        let index = [
+            4, 99, 117, 115, 116, // "cust"
+            1, 4, 10, 1, 0, 0,  // ipv4 10.1.0.0
+            16, // prefix length
+            2, 0, 0, // policy 0.0
+            1, 4, 192, 168, 0, 1, // ipv4 192.168.0.1
+        ];
+        let (vrf, route) = route_from_index(&index, 7).unwrap();
+        assert_eq!(vrf, "cust");
+        assert_eq!(route.destination, "10.1.0.0".parse::<IpAddr>().unwrap());
+        assert_eq!(route.netmask, 16);
+        assert_eq!(route.next_hop, "192.168.0.1".parse::<IpAddr>().unwrap());
+        assert_eq!(route.if_index, 7);
+    }
+}
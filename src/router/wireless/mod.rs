@@ -0,0 +1,282 @@
+use super::Vendor;
+use crate::config::CONFIG;
+use crate::csnmp::ObjectValue;
+use crate::query_engine::{
+    as_int, as_ip, as_mac, as_string, as_u32, format_mac, oid_index, snmp_query,
+};
+use anyhow::{bail, Result};
+use serde::{Deserialize, Serialize};
+use std::{collections::BTreeMap, net::IpAddr};
+mod tables;
+
+/// Where to find per-link radio statistics in a vendor MIB. Each field
+/// (other than the table and offsets) is a column number within the
+/// table; leave out whatever the vendor doesn't provide.
+#[derive(Debug, Clone, Deserialize)]
+#[serde(default)]
+pub struct WirelessTable {
+    pub name: String,
+    /// Only walked on devices fingerprinted as this vendor
+    pub vendor: Vendor,
+    /// The table's entry OID (the columns are numbered below this)
+    pub table: String,
+    /// The peer's MAC is in the row index, starting at this sub-identifier
+    pub mac_index_offset: Option<usize>,
+    /// ...or in this column
+    pub mac_column: Option<u32>,
+    /// The local ifIndex is in the row index at this sub-identifier
+    pub interface_index_offset: Option<usize>,
+    pub remote_address: Option<u32>,
+    /// dBm
+    pub signal: Option<u32>,
+    /// dBm
+    pub noise: Option<u32>,
+    /// dB
+    pub snr: Option<u32>,
+    /// Percent
+    pub ccq: Option<u32>,
+    pub modulation: Option<u32>,
+    pub tx_rate: Option<u32>,
+    pub rx_rate: Option<u32>,
+    pub tx_capacity: Option<u32>,
+    pub rx_capacity: Option<u32>,
+    /// Multiply rate and capacity columns by this to get bits per second
+    pub rate_unit_bps: f64,
+}
+
+impl Default for WirelessTable {
+    fn default() -> Self {
+        Self {
+            name: String::new(),
+            vendor: Vendor::Unknown,
+            table: String::new(),
+            mac_index_offset: None,
+            mac_column: None,
+            interface_index_offset: None,
+            remote_address: None,
+            signal: None,
+            noise: None,
+            snr: None,
+            ccq: None,
+            modulation: None,
+            tx_rate: None,
+            rx_rate: None,
+            tx_capacity: None,
+            rx_capacity: None,
+            rate_unit_bps: 1.0,
+        }
+    }
+}
+
+/// Radio statistics for one wireless link, as seen from this end.
+#[derive(Debug, Clone, Default, PartialEq, Serialize)]
+pub struct WirelessLink {
+    /// The table the link was read from
+    pub source: String,
+    pub local_interface: Option<i32>,
+    pub remote_mac: String,
+    pub remote_address: Option<IpAddr>,
+    pub signal_dbm: Option<f64>,
+    pub noise_dbm: Option<f64>,
+    pub snr_db: Option<f64>,
+    pub ccq_percent: Option<f64>,
+    pub modulation: String,
+    pub tx_rate_mbps: Option<f64>,
+    pub rx_rate_mbps: Option<f64>,
+    pub tx_capacity_mbps: Option<f64>,
+    pub rx_capacity_mbps: Option<f64>,
+}
+
+impl WirelessLink {
+    /// Signal-to-noise ratio, reported or worked out from signal and noise.
+    pub fn snr(&self) -> Option<f64> {
+        self.snr_db
+            .or_else(|| Some(self.signal_dbm? - self.noise_dbm?))
+    }
+
+    /// What the link can carry in each direction (tx, rx): the radio's
+    /// capacity estimate if it gives a usable one, otherwise the current
+    /// PHY rates. Zero and non-finite figures count as missing.
+    pub fn capacity_mbps(&self) -> (Option<f64>, Option<f64>) {
+        let usable = |mbps: Option<f64>| mbps.filter(|m| m.is_finite() && *m > 0.0);
+        (
+            usable(self.tx_capacity_mbps).or(usable(self.tx_rate_mbps)),
+            usable(self.rx_capacity_mbps).or(usable(self.rx_rate_mbps)),
+        )
+    }
+
+    /// The same link, seen from the other end.
+    pub fn reversed(&self) -> Self {
+        Self {
+            local_interface: None,
+            tx_rate_mbps: self.rx_rate_mbps,
+            rx_rate_mbps: self.tx_rate_mbps,
+            tx_capacity_mbps: self.rx_capacity_mbps,
+            rx_capacity_mbps: self.tx_capacity_mbps,
+            ..self.clone()
+        }
+    }
+}
+
+#[derive(Debug, Default, Serialize)]
+pub struct Wireless {
+    pub links: Vec<WirelessLink>,
+}
+
+fn as_number(value: &ObjectValue) -> Option<f64> {
+    if let Ok(i) = as_int(value) {
+        return Some(i as f64);
+    }
+    if let Ok(u) = as_u32(value) {
+        return Some(u as f64);
+    }
+    as_string(value).ok()?.trim().parse().ok()
+}
+
+impl WirelessTable {
+    fn rate(&self, value: &ObjectValue) -> Option<f64> {
+        Some(as_number(value)? * self.rate_unit_bps / 1_000_000.0)
+    }
+
+    async fn load(&self, ip_address: &str, community: &str) -> Result<Vec<WirelessLink>> {
+        Ok(self.decode(snmp_query(ip_address, community, &self.table).await?))
+    }
+
+    /// Build links from a walk of the table.
+    fn decode(&self, walk: Vec<(String, ObjectValue)>) -> Vec<WirelessLink> {
+        // Keyed by the row index
+        let mut rows: BTreeMap<Vec<u32>, WirelessLink> = BTreeMap::new();
+        for (oid, val) in walk {
+            let Some(index) = oid_index(&oid, &self.table) else {
+                continue;
+            };
+            let Some((column, row)) = index.split_first() else {
+                continue;
+            };
+            let link = rows.entry(row.to_vec()).or_insert_with(|| {
+                let from_index = |offset: Option<usize>, len: usize| {
+                    let offset = offset?;
+                    row.get(offset..offset + len)
+                };
+                WirelessLink {
+                    source: self.name.clone(),
+                    local_interface: from_index(self.interface_index_offset, 1)
+                        .map(|i| i[0] as i32),
+                    remote_mac: from_index(self.mac_index_offset, 6)
+                        .map(|mac| {
+                            let bytes: Vec<u8> = mac.iter().map(|b| *b as u8).collect();
+                            format_mac(&bytes)
+                        })
+                        .unwrap_or_default(),
+                    ..Default::default()
+                }
+            });
+            let column = Some(*column);
+            if column == self.mac_column {
+                link.remote_mac = as_mac(&val).unwrap_or_default();
+            } else if column == self.remote_address {
+                link.remote_address = as_ip(&val).ok().filter(|a| !a.is_unspecified());
+            } else if column == self.signal {
+                link.signal_dbm = as_number(&val);
+            } else if column == self.noise {
+                link.noise_dbm = as_number(&val);
+            } else if column == self.snr {
+                link.snr_db = as_number(&val);
+            } else if column == self.ccq {
+                link.ccq_percent = as_number(&val);
+            } else if column == self.modulation {
+                link.modulation = as_string(&val).unwrap_or_default();
+            } else if column == self.tx_rate {
+                link.tx_rate_mbps = self.rate(&val);
+            } else if column == self.rx_rate {
+                link.rx_rate_mbps = self.rate(&val);
+            } else if column == self.tx_capacity {
+                link.tx_capacity_mbps = self.rate(&val);
+            } else if column == self.rx_capacity {
+                link.rx_capacity_mbps = self.rate(&val);
+            }
+        }
+        rows.into_values().collect()
+    }
+}
+
+impl Wireless {
+    /// Walk every wireless table (built-in or configured) known for
+    /// `vendor`.
+    pub(crate) async fn from_snmp(
+        ip_address: &str,
+        community: &str,
+        vendor: Vendor,
+    ) -> Result<Self> {
+        let tables: Vec<WirelessTable> = tables::built_in()
+            .into_iter()
+            .chain(CONFIG.wireless_tables.iter().cloned())
+            .filter(|t| t.vendor == vendor)
+            .collect();
+        if tables.is_empty() {
+            return Ok(Self::default());
+        }
+
+        let mut links = Vec::new();
+        let mut errors = Vec::new();
+        for table in tables.iter() {
+            match table.load(ip_address, community).await {
+                Ok(found) => links.extend(found),
+                Err(e) => errors.push(format!("{}: {e}", table.name)),
+            }
+        }
+        if links.is_empty() && errors.len() == tables.len() {
+            bail!("No wireless tables could be read ({})", errors.join(", "));
+        }
+        Ok(Self { links })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_link_figures() {
+        let link = WirelessLink {
+            signal_dbm: Some(-62.0),
+            noise_dbm: Some(-95.0),
+            tx_rate_mbps: Some(300.0),
+            rx_rate_mbps: Some(270.0),
+            rx_capacity_mbps: Some(180.0),
+            ..Default::default()
+        };
+        assert_eq!(link.snr(), Some(33.0));
+        assert_eq!(link.capacity_mbps(), (Some(300.0), Some(180.0)));
+        assert_eq!(link.reversed().capacity_mbps(), (Some(180.0), Some(300.0)));
+    }
+
+    #[test]
+    fn test_airmax_station_columns() {
+        let table = tables::built_in()
+            .into_iter()
+            .find(|t| t.name == "airMAX stations")
+            .unwrap();
+        // ubntStaEntry column, ubntWlStatIndex 1, station MAC
+        let oid = |column: u32| format!("{}.{column}.1.4.94.12.18.52.86", table.table);
+        let walk = vec![
+            (oid(3), ObjectValue::Integer(-58)),
+            // ubntStaAmc, which isn't an address
+            (oid(9), ObjectValue::Integer(2)),
+            (
+                oid(10),
+                ObjectValue::IpAddress("10.20.0.5".parse().unwrap()),
+            ),
+            (oid(11), ObjectValue::Integer(300_000)),
+            (oid(12), ObjectValue::Integer(270_000)),
+        ];
+        let links = table.decode(walk);
+        assert_eq!(links.len(), 1);
+        let link = &links[0];
+        assert_eq!(link.remote_mac, "04:5E:0C:12:34:56");
+        assert_eq!(link.remote_address, Some("10.20.0.5".parse().unwrap()));
+        assert_eq!(link.signal_dbm, Some(-58.0));
+        assert_eq!(link.tx_rate_mbps, Some(300.0));
+        assert_eq!(link.rx_rate_mbps, Some(270.0));
+    }
+}
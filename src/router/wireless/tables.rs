@@ -0,0 +1,81 @@
+//! Built-in wireless table definitions. More can be added from
+//! `router_mapper.toml` without recompiling.
+use super::WirelessTable;
+use crate::router::Vendor;
+
+pub(super) fn built_in() -> Vec<WirelessTable> {
+    vec![
+        // MIKROTIK-MIB mtxrWlRtabTable: one row per registered peer,
+        // indexed by mac.ifIndex. Rates are in bits per second.
+        WirelessTable {
+            name: "MikroTik registration table".to_string(),
+            vendor: Vendor::MikroTik,
+            table: "1.3.6.1.4.1.14988.1.1.1.2.1".to_string(),
+            mac_index_offset: Some(0),
+            interface_index_offset: Some(6),
+            signal: Some(3),
+            tx_rate: Some(8),
+            rx_rate: Some(9),
+            snr: Some(12),
+            ..Default::default()
+        },
+        // UBNT-AirMAX-MIB ubntStaTable: the stations registered to an AP,
+        // indexed by ubntWlStatIndex.mac. Rates are in kbps.
+        WirelessTable {
+            name: "airMAX stations".to_string(),
+            vendor: Vendor::Ubiquiti,
+            table: "1.3.6.1.4.1.41112.1.4.7.1".to_string(),
+            mac_index_offset: Some(1),
+            // ubntStaLastIp
+            remote_address: Some(10),
+            signal: Some(3),
+            noise: Some(4),
+            ccq: Some(6),
+            // ubntStaTxRate, ubntStaRxRate
+            tx_rate: Some(11),
+            rx_rate: Some(12),
+            rate_unit_bps: 1000.0,
+            ..Default::default()
+        },
+        // UBNT-AirMAX-MIB ubntWlStatTable: a station's view of its AP
+        WirelessTable {
+            name: "airMAX station status".to_string(),
+            vendor: Vendor::Ubiquiti,
+            table: "1.3.6.1.4.1.41112.1.4.5.1".to_string(),
+            mac_column: Some(4),
+            signal: Some(5),
+            ccq: Some(7),
+            noise: Some(8),
+            tx_rate: Some(9),
+            rx_rate: Some(10),
+            rate_unit_bps: 1000.0,
+            ..Default::default()
+        },
+        // UBNT-AF-MIB airFiberStatusTable. Capacities are in bits per second.
+        WirelessTable {
+            name: "airFiber status".to_string(),
+            vendor: Vendor::Ubiquiti,
+            table: "1.3.6.1.4.1.41112.1.3.2.1".to_string(),
+            mac_column: Some(45),
+            remote_address: Some(46),
+            modulation: Some(2),
+            rx_capacity: Some(5),
+            tx_capacity: Some(6),
+            signal: Some(11),
+            ..Default::default()
+        },
+        // CAMBIUM-PMP80211-MIB (ePMP) cambiumAPConnectedSTAListTable
+        WirelessTable {
+            name: "ePMP connected stations".to_string(),
+            vendor: Vendor::Cambium,
+            table: "1.3.6.1.4.1.17713.21.1.2.30.1".to_string(),
+            mac_column: Some(1),
+            remote_address: Some(3),
+            signal: Some(7),
+            snr: Some(8),
+            modulation: Some(11),
+            rate_unit_bps: 1000.0,
+            ..Default::default()
+        },
+    ]
+}
@@ -10,6 +10,12 @@ use tracing::{error, info};
 pub struct RouterTarget {
     pub ip_address: String,
     pub community: String,
+    /// Map the router using this VRF's routing table instead of the global one
+    #[serde(default)]
+    pub vrf: Option<String>,
+    /// Group the router under this site instead of its sysLocation
+    #[serde(default)]
+    pub site: Option<String>,
 }
 
 #[derive(Debug)]
@@ -31,6 +37,7 @@ impl RouterList {
         let reader = ReaderBuilder::new()
             .comment(Some(b'#'))
             .trim(csv::Trim::All)
+            .flexible(true)
             .from_path(path)?;
 
         for line in reader.into_records() {
@@ -45,13 +52,14 @@ impl RouterList {
     async fn router_builder_with_retries(
         ip: String,
         community: String,
+        vrf: Option<String>,
         retries: u32,
     ) -> Result<Router> {
         for attempt in 0..retries {
             if attempt > 0 {
                 info!("Retrying {ip} (attempt {} of {retries})...", attempt + 1);
             }
-            match router_builder(ip.clone(), community.clone()).await {
+            match router_builder(ip.clone(), community.clone(), vrf.clone()).await {
                 Ok(router) => return Ok(router),
                 Err(e) => error!("Error fetching SNMP data from {ip}: {e}"),
             }
@@ -65,8 +73,13 @@ impl RouterList {
         for target in self.targets.iter() {
             let ip = target.ip_address.clone();
             let community = target.community.clone();
-            let router = Self::router_builder_with_retries(ip, community, 3);
-            set.spawn(router);
+            let vrf = target.vrf.clone();
+            let site = target.site.clone();
+            set.spawn(async move {
+                let mut router = Self::router_builder_with_retries(ip, community, vrf, 3).await?;
+                router.connection.site = site;
+                Ok::<_, anyhow::Error>(router)
+            });
         }
 
         let mut results = Vec::new();
@@ -0,0 +1,145 @@
+use super::{EdgeKind, Evidence, NodeId, Topology};
+use crate::router::{BgpPeer, BgpPeerState};
+
+// An established session is certain; a configured-but-down one less so
+const ESTABLISHED_CONFIDENCE: f32 = 1.0;
+const CONFIGURED_CONFIDENCE: f32 = 0.6;
+
+impl Topology {
+    /// Add an edge for every BGP session between two known routers.
+    pub(crate) fn infer_bgp_edges(&mut self) {
+        let mut found = Vec::new();
+        for node in self.nodes.iter() {
+            let bgp = &node.router.bgp;
+            for peer in bgp.peers.iter() {
+                let Some(other) = self.find_bgp_peer(peer, node.id) else {
+                    continue;
+                };
+                let confidence = if peer.state == BgpPeerState::Established {
+                    ESTABLISHED_CONFIDENCE
+                } else {
+                    CONFIGURED_CONFIDENCE
+                };
+                found.push((
+                    node.id,
+                    other,
+                    confidence,
+                    Evidence::BgpSession {
+                        local_as: bgp.local_as.unwrap_or_default(),
+                        remote_as: peer.remote_as,
+                        state: peer.state,
+                        internal: bgp.is_ibgp(peer),
+                    },
+                ));
+            }
+        }
+        for (from, to, confidence, evidence) in found {
+            self.add_edge(from, to, EdgeKind::BgpPeer, confidence, evidence);
+        }
+    }
+
+    /// Match a BGP peer to a known router, by session address or router-id.
+    /// An unspecified (0.0.0.0) router-id, as reported before a session
+    /// comes up, never matches.
+    pub fn find_bgp_peer(&self, peer: &BgpPeer, seen_by: NodeId) -> Option<NodeId> {
+        self.find_owner(peer.remote_address, Some(seen_by))
+            .or_else(|| {
+                if peer.remote_router_id.is_unspecified() {
+                    return None;
+                }
+                self.nodes
+                    .iter()
+                    .find(|n| {
+                        n.id != seen_by && n.router.bgp.router_id == Some(peer.remote_router_id)
+                    })
+                    .map(|n| n.id)
+            })
+    }
+
+    /// Known routers that `node` has a BGP session with.
+    pub fn bgp_peers(&self, node: NodeId) -> Vec<NodeId> {
+        self.edges_of(node)
+            .filter(|e| e.kind == EdgeKind::BgpPeer)
+            .map(|e| e.other_end(node))
+            .collect()
+    }
+
+    /// Routers that look like iBGP route reflectors: they hold internal
+    /// sessions with more known routers than any of those routers do with
+    /// each other. Returned with their clients, busiest first.
+    pub fn route_reflectors(&self) -> Vec<(NodeId, Vec<NodeId>)> {
+        let ibgp_peers = |node: NodeId| -> Vec<NodeId> {
+            let bgp = &self.nodes[node].router.bgp;
+            self.bgp_peers(node)
+                .into_iter()
+                .filter(|peer| {
+                    let peer_bgp = &self.nodes[*peer].router.bgp;
+                    bgp.local_as.is_some() && bgp.local_as == peer_bgp.local_as
+                })
+                .collect()
+        };
+        let mut reflectors: Vec<(NodeId, Vec<NodeId>)> = self
+            .nodes
+            .iter()
+            .map(|n| (n.id, ibgp_peers(n.id)))
+            .filter(|(_, peers)| peers.len() > 1)
+            .filter(|(_, peers)| peers.iter().all(|p| ibgp_peers(*p).len() < peers.len()))
+            .collect();
+        reflectors.sort_by_key(|(_, clients)| std::cmp::Reverse(clients.len()));
+        reflectors
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::router::fixtures::router;
+
+    fn peering(remote: &str) -> BgpPeer {
+        let mut peer = BgpPeer::new(remote.parse().unwrap());
+        peer.remote_as = 65000;
+        peer.state = BgpPeerState::Established;
+        peer
+    }
+
+    #[test]
+    fn test_route_reflector() {
+        let mut rr = router("rr", &["10.255.0.1/32"], vec![]);
+        rr.bgp.local_as = Some(65000);
+        rr.bgp.peers = vec![
+            peering("10.255.0.2"),
+            peering("10.255.0.3"),
+            peering("10.255.0.4"),
+        ];
+        let mut routers = vec![rr];
+        for i in 2..=4 {
+            let mut client = router(&format!("pe{i}"), &[&format!("10.255.0.{i}/32")], vec![]);
+            client.bgp.local_as = Some(65000);
+            client.bgp.peers = vec![peering("10.255.0.1")];
+            routers.push(client);
+        }
+        let mut topology = Topology::from_routers(routers);
+        topology.infer_bgp_edges();
+        // Sessions seen from both ends are merged into one edge each
+        assert_eq!(topology.edges.len(), 3);
+        assert_eq!(topology.bgp_peers(2), vec![0]);
+        assert_eq!(topology.route_reflectors(), vec![(0, vec![1, 2, 3])]);
+    }
+
+    #[test]
+    fn test_unspecified_router_id() {
+        // A down session to an unknown address reports a 0.0.0.0 router-id,
+        // which mustn't match a router whose own router-id is unset
+        let mut a = router("a", &["10.255.0.1/32"], vec![]);
+        let mut peer = peering("192.0.2.1");
+        peer.state = BgpPeerState::Active;
+        a.bgp.peers = vec![peer];
+        let mut b = router("b", &["10.255.0.2/32"], vec![]);
+        b.bgp.router_id = Some("0.0.0.0".parse().unwrap());
+        let topology = Topology::from_routers(vec![a, b]);
+        assert_eq!(
+            topology.find_bgp_peer(&topology.nodes[0].router.bgp.peers[0], 0),
+            None
+        );
+    }
+}
@@ -0,0 +1,89 @@
+use super::{NodeId, Topology};
+use serde::Serialize;
+
+/// Where a bridge has learned a MAC address.
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct MacLocation {
+    /// The bridge (switch, bridged radio, router with a bridge)
+    pub node: NodeId,
+    pub port: u32,
+    pub if_index: Option<i32>,
+    pub vlan: Option<u32>,
+    /// Distinct MACs learned on the same port; fewer means closer to the
+    /// device
+    pub macs_on_port: usize,
+}
+
+impl Topology {
+    /// Every bridge (other than `except`) that has learned `mac`, nearest
+    /// first: the port that has learned the fewest other MACs is most
+    /// likely the one the device is plugged into, while uplinks see
+    /// nearly everything.
+    pub fn locate_mac(&self, mac: &str, except: Option<NodeId>) -> Vec<MacLocation> {
+        let mut locations: Vec<MacLocation> = self
+            .nodes
+            .iter()
+            .filter(|n| Some(n.id) != except)
+            .flat_map(|n| {
+                n.router.bridge.find_mac(mac).map(|entry| MacLocation {
+                    node: n.id,
+                    port: entry.port,
+                    if_index: entry.if_index,
+                    vlan: entry.vlan,
+                    macs_on_port: n.router.bridge.macs_on_port(entry.port),
+                })
+            })
+            .collect();
+        locations.sort_by_key(|l| (l.macs_on_port, l.node, l.port));
+        locations.dedup_by_key(|l| (l.node, l.port));
+        locations
+    }
+
+    /// The bridge port that `node` appears to be plugged into, found by
+    /// looking up its interfaces' MAC addresses.
+    pub fn attachment_port(&self, node: NodeId) -> Option<MacLocation> {
+        self.nodes[node]
+            .router
+            .interfaces
+            .interfaces
+            .iter()
+            .filter(|i| !i.phys_address.is_empty())
+            .flat_map(|i| self.locate_mac(&i.phys_address, Some(node)))
+            .min_by_key(|l| l.macs_on_port)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::router::{
+        fixtures::{learned, router},
+        Interface,
+    };
+
+    #[test]
+    fn test_attachment_port() {
+        let mut switch = router("switch", &["10.0.0.2/24"], vec![]);
+        switch.bridge.set_fdb(vec![
+            // Port 1 is the uplink: it sees everything
+            learned("00:00:00:00:00:01", 1),
+            learned("00:00:00:00:00:02", 1),
+            learned("00:00:00:00:00:03", 1),
+            learned("00:00:00:00:00:AA", 1),
+            // Port 5 only sees the downstream router
+            learned("00:00:00:00:00:AA", 5),
+        ]);
+        let mut downstream = router("cpe", &["10.0.0.3/24"], vec![]);
+        downstream.interfaces.interfaces.push(Interface {
+            index: 1,
+            phys_address: "00:00:00:00:00:aa".to_string(),
+            ..Default::default()
+        });
+        let topology = Topology::from_routers(vec![switch, downstream]);
+        let location = topology.attachment_port(1).unwrap();
+        assert_eq!(location.node, 0);
+        assert_eq!(location.port, 5);
+        assert_eq!(location.if_index, Some(105));
+        assert_eq!(topology.locate_mac("00:00:00:00:00:AA", None).len(), 2);
+    }
+}
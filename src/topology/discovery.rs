@@ -0,0 +1,115 @@
+use super::{NodeId, Topology};
+use crate::router::{ArpEntry, NeighborProtocol, Router};
+use serde::Serialize;
+use std::{collections::BTreeMap, net::IpAddr};
+
+/// Why we think an unlisted address is a managed device rather than a host.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+pub enum DiscoveryReason {
+    /// It announces itself with a neighbor discovery protocol
+    Neighbor(NeighborProtocol),
+    /// A known router routes traffic via it
+    NextHop,
+}
+
+/// A device that appears in a known router's ARP/neighbor cache and looks
+/// like managed network gear, but isn't in `router_list.csv`.
+#[derive(Debug, Clone, Serialize)]
+pub struct UnlistedDevice {
+    pub address: IpAddr,
+    pub mac: String,
+    /// The name it announces, if any
+    pub name: String,
+    pub seen_by: NodeId,
+    pub if_index: i32,
+    pub reason: DiscoveryReason,
+}
+
+fn classify(router: &Router, entry: &ArpEntry) -> Option<(DiscoveryReason, String)> {
+    if let Some(neighbor) = router.neighbors.neighbors.iter().find(|n| {
+        n.management_addresses.contains(&entry.address)
+            || n.chassis_id.eq_ignore_ascii_case(&entry.mac)
+    }) {
+        return Some((
+            DiscoveryReason::Neighbor(neighbor.protocol),
+            neighbor.system_name.clone(),
+        ));
+    }
+    if router
+        .ip_routes
+        .routes
+        .iter()
+        .any(|r| r.next_hop == entry.address)
+    {
+        return Some((DiscoveryReason::NextHop, String::new()));
+    }
+    None
+}
+
+impl Topology {
+    /// Look through every router's ARP cache for managed devices that we
+    /// weren't asked to poll.
+    pub(crate) fn discover_unlisted_devices(&mut self) {
+        let mut found: BTreeMap<IpAddr, UnlistedDevice> = BTreeMap::new();
+        for node in self.nodes.iter() {
+            for entry in node.router.arp_table.neighbors() {
+                if found.contains_key(&entry.address)
+                    || self.find_owner(entry.address, None).is_some()
+                    || self.nodes.iter().any(|n| n.router.has_mac(&entry.mac))
+                {
+                    continue;
+                }
+                let Some((reason, name)) = classify(&node.router, entry) else {
+                    continue;
+                };
+                found.insert(
+                    entry.address,
+                    UnlistedDevice {
+                        address: entry.address,
+                        mac: entry.mac.clone(),
+                        name,
+                        seen_by: node.id,
+                        if_index: entry.if_index,
+                        reason,
+                    },
+                );
+            }
+        }
+        self.unlisted_devices = found.into_values().collect();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::router::fixtures::{arp, route, router};
+
+    #[test]
+    fn test_unlisted_next_hop() {
+        let mut a = router(
+            "a",
+            &["10.0.0.1/24"],
+            vec![route("192.168.0.0", 16, "10.0.0.9")],
+        );
+        a.arp_table.entries = vec![
+            // A known router
+            arp("10.0.0.2", "00:00:00:00:00:02"),
+            // A router we route through, but don't poll
+            arp("10.0.0.9", "00:00:00:00:00:09"),
+            // Just a host
+            arp("10.0.0.50", "00:00:00:00:00:50"),
+        ];
+        let b = router("b", &["10.0.0.2/24"], vec![]);
+        let mut topology = Topology::from_routers(vec![a, b]);
+        topology.discover_unlisted_devices();
+        assert_eq!(topology.unlisted_devices.len(), 1);
+        assert_eq!(
+            topology.unlisted_devices[0].address,
+            "10.0.0.9".parse::<IpAddr>().unwrap()
+        );
+        assert_eq!(
+            topology.unlisted_devices[0].reason,
+            DiscoveryReason::NextHop
+        );
+    }
+}
@@ -0,0 +1,118 @@
+use super::NodeId;
+use crate::router::{
+    BgpPeerState, InterfaceRates, NeighborProtocol, NextHopResolution, OspfNeighborState,
+    UpstreamRoute, WirelessLink,
+};
+use ipnetwork::IpNetwork;
+use serde::Serialize;
+use std::net::Ipv4Addr;
+
+/// The kind of relationship an edge represents.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize)]
+pub enum EdgeKind {
+    /// `from`'s upstream (usually default) route points at `to`
+    Parent,
+    /// `from` and `to` both have an address in the same connected subnet
+    ConnectedSubnet,
+    /// `from` sees `to` as a neighbor via LLDP, CDP or MNDP
+    Neighbor,
+    /// `from` has a BGP session with `to`
+    BgpPeer,
+    /// `from` and `to` are OSPF neighbors
+    OspfAdjacency,
+    /// `from` has `to` in a radio registration table
+    Wireless,
+}
+
+impl EdgeKind {
+    /// Parent edges are directional; everything else describes a link
+    /// between two peers.
+    pub fn is_directed(&self) -> bool {
+        matches!(self, Self::Parent)
+    }
+}
+
+/// Why we believe an edge exists.
+#[derive(Debug, Clone, Serialize)]
+#[serde(tag = "type")]
+pub enum Evidence {
+    /// The child's upstream route, and the recursive next-hop resolution
+    /// performed on it (if any)
+    UpstreamRoute {
+        route: UpstreamRoute,
+        resolution: Option<NextHopResolution>,
+    },
+    /// Both routers have an address in this connected subnet
+    SharedSubnet { subnet: IpNetwork },
+    /// A neighbor discovery protocol saw the other router on `local_port`
+    Neighbor {
+        protocol: NeighborProtocol,
+        local_port: String,
+        remote_port: String,
+    },
+    /// `from` has a BGP session configured towards `to`
+    BgpSession {
+        local_as: u32,
+        remote_as: u32,
+        state: BgpPeerState,
+        internal: bool,
+    },
+    /// `from` lists `to` in its OSPF neighbor table
+    OspfAdjacency {
+        area: Option<Ipv4Addr>,
+        state: OspfNeighborState,
+    },
+    /// Radio statistics for the link, as seen from `from`
+    Wireless { link: WirelessLink },
+}
+
+/// Measured load on the interface carrying an edge, seen from `from`.
+#[derive(Debug, Clone, Serialize)]
+pub struct Utilisation {
+    /// The interface the counters were read from (on `from` if it had
+    /// them, otherwise on `to`)
+    pub interface: String,
+    pub capacity_mbps: u64,
+    pub rates: InterfaceRates,
+    /// The busier direction as a fraction of capacity, if capacity is known
+    pub fraction: Option<f64>,
+}
+
+/// A typed relationship between two routers.
+#[derive(Debug, Clone, Serialize)]
+pub struct Edge {
+    pub from: NodeId,
+    pub to: NodeId,
+    pub kind: EdgeKind,
+    /// How sure we are, from 0.0 (guess) to 1.0 (confirmed)
+    pub confidence: f32,
+    pub evidence: Vec<Evidence>,
+    /// Traffic on the link, if counters were sampled
+    pub utilisation: Option<Utilisation>,
+}
+
+impl Edge {
+    /// Does this edge connect `a` and `b` (in either direction for
+    /// undirected kinds)?
+    pub fn connects(&self, a: NodeId, b: NodeId) -> bool {
+        (self.from == a && self.to == b)
+            || (!self.kind.is_directed() && self.from == b && self.to == a)
+    }
+
+    /// Radio statistics, if this is a wireless link.
+    pub fn wireless(&self) -> Option<&WirelessLink> {
+        self.evidence.iter().find_map(|e| match e {
+            Evidence::Wireless { link } => Some(link),
+            _ => None,
+        })
+    }
+
+    /// The node at the other end of the edge from `node`.
+    pub fn other_end(&self, node: NodeId) -> NodeId {
+        if self.from == node {
+            self.to
+        } else {
+            self.from
+        }
+    }
+}
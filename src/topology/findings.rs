@@ -0,0 +1,332 @@
+use super::{EdgeKind, NodeId, Topology};
+use crate::router::{ResolutionOutcome, UpstreamRoute};
+use serde::Serialize;
+use std::collections::{BTreeMap, HashSet};
+
+/// What kind of problem a finding describes.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+pub enum FindingKind {
+    /// Parent relationships form a cycle (A -> B -> ... -> A)
+    RoutingLoop,
+    /// A router's upstream next hop is one of its own addresses
+    SelfParent,
+    /// A router's upstream next hop doesn't belong to any known router
+    OrphanedRouter,
+    /// Recursive next-hop resolution looped or gave up
+    UnresolvedNextHop,
+    /// Several routers report the same sysName
+    DuplicateHostname,
+    /// A managed device seen in an ARP cache isn't in the router list
+    UnlistedDevice,
+    /// A router's CPU, memory, storage or temperature is over its threshold
+    Overloaded,
+    /// The VRF a router was listed with has no routes, so its parent was
+    /// found from the global table instead
+    EmptyVrf,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Serialize)]
+pub enum Severity {
+    Info,
+    Warning,
+    Error,
+}
+
+/// An inconsistency found in the inferred hierarchy.
+#[derive(Debug, Clone, Serialize)]
+pub struct Finding {
+    pub kind: FindingKind,
+    pub severity: Severity,
+    /// The routers involved
+    pub nodes: Vec<NodeId>,
+    /// The upstream routes involved, by router
+    pub routes: Vec<(NodeId, UpstreamRoute)>,
+    pub message: String,
+}
+
+impl Topology {
+    /// Check the inferred hierarchy for loops and inconsistencies.
+    pub(crate) fn detect_findings(&mut self) {
+        let mut findings = Vec::new();
+        findings.extend(self.find_routing_loops());
+        findings.extend(self.find_upstream_problems());
+        findings.extend(self.find_duplicate_hostnames());
+        findings.extend(self.unlisted_device_findings());
+        findings.extend(self.find_overloaded_routers());
+        findings.extend(self.find_empty_vrfs());
+        self.findings = findings;
+    }
+
+    /// Log every finding.
+    pub fn log_findings(&self) {
+        for finding in self.findings.iter() {
+            match finding.severity {
+                Severity::Info => tracing::info!("{:?}: {}", finding.kind, finding.message),
+                Severity::Warning => tracing::warn!("{:?}: {}", finding.kind, finding.message),
+                Severity::Error => tracing::error!("{:?}: {}", finding.kind, finding.message),
+            }
+        }
+    }
+
+    /// Findings that involve `node`.
+    pub fn findings_for(&self, node: NodeId) -> impl Iterator<Item = &Finding> {
+        self.findings
+            .iter()
+            .filter(move |f| f.nodes.contains(&node))
+    }
+
+    fn names(&self, nodes: &[NodeId]) -> String {
+        nodes
+            .iter()
+            .map(|n| self.nodes[*n].name.as_str())
+            .collect::<Vec<_>>()
+            .join(" -> ")
+    }
+
+    fn upstream_routes(&self, nodes: &[NodeId]) -> Vec<(NodeId, UpstreamRoute)> {
+        nodes
+            .iter()
+            .filter_map(|n| self.nodes[*n].upstream.clone().map(|r| (*n, r)))
+            .collect()
+    }
+
+    fn find_overloaded_routers(&self) -> Vec<Finding> {
+        self.nodes
+            .iter()
+            .filter_map(|node| {
+                let problems = node.router.health.problems();
+                if problems.is_empty() {
+                    return None;
+                }
+                Some(Finding {
+                    kind: FindingKind::Overloaded,
+                    severity: Severity::Warning,
+                    nodes: vec![node.id],
+                    routes: Vec::new(),
+                    message: format!("{}: {}", node.name, problems.join(", ")),
+                })
+            })
+            .collect()
+    }
+
+    fn find_empty_vrfs(&self) -> Vec<Finding> {
+        self.nodes
+            .iter()
+            .filter_map(|node| {
+                let router = &node.router;
+                let name = router.connection.vrf.as_ref()?;
+                if router
+                    .vrfs
+                    .get(name)
+                    .is_some_and(|vrf| !vrf.routes.routes.is_empty())
+                {
+                    return None;
+                }
+                Some(Finding {
+                    kind: FindingKind::EmptyVrf,
+                    severity: Severity::Warning,
+                    nodes: vec![node.id],
+                    routes: Vec::new(),
+                    message: format!(
+                        "{}: no routes in VRF {name}, used the global table",
+                        node.name
+                    ),
+                })
+            })
+            .collect()
+    }
+
+    fn find_routing_loops(&self) -> Vec<Finding> {
+        let mut findings = Vec::new();
+        let mut seen_cycles: HashSet<Vec<NodeId>> = HashSet::new();
+
+        for node in self.nodes.iter() {
+            // Walk up the preferred parents until we hit a root or repeat
+            let mut path = vec![node.id];
+            let mut current = node.id;
+            while let Some(parent) = self.preferred_parent(current) {
+                if let Some(start) = path.iter().position(|n| *n == parent) {
+                    let cycle = path[start..].to_vec();
+                    let mut key = cycle.clone();
+                    key.sort();
+                    if seen_cycles.insert(key) {
+                        let mut names = cycle.clone();
+                        names.push(parent);
+                        findings.push(Finding {
+                            kind: FindingKind::RoutingLoop,
+                            severity: Severity::Error,
+                            routes: self.upstream_routes(&cycle),
+                            message: format!("Routing loop: {}", self.names(&names)),
+                            nodes: cycle,
+                        });
+                    }
+                    break;
+                }
+                path.push(parent);
+                current = parent;
+            }
+        }
+        findings
+    }
+
+    fn find_upstream_problems(&self) -> Vec<Finding> {
+        let mut findings = Vec::new();
+        for node in self.nodes.iter() {
+            let (Some(upstream), Some(parent_address)) = (&node.upstream, node.parent_address())
+            else {
+                continue;
+            };
+            let routes = vec![(node.id, upstream.clone())];
+
+            if let Some(resolution) = &node.resolution {
+                if let ResolutionOutcome::Loop(_) | ResolutionOutcome::DepthExceeded(_) =
+                    resolution.outcome
+                {
+                    findings.push(Finding {
+                        kind: FindingKind::UnresolvedNextHop,
+                        severity: Severity::Warning,
+                        nodes: vec![node.id],
+                        routes: routes.clone(),
+                        message: format!(
+                            "{}: resolving next hop {} ended with {:?} after {} steps",
+                            node.name,
+                            upstream.next_hop,
+                            resolution.outcome,
+                            resolution.steps.len()
+                        ),
+                    });
+                }
+            }
+
+            let has_parent = self
+                .edges
+                .iter()
+                .any(|e| e.kind == EdgeKind::Parent && e.from == node.id);
+            if node.router.owns_address(parent_address) {
+                findings.push(Finding {
+                    kind: FindingKind::SelfParent,
+                    severity: Severity::Warning,
+                    nodes: vec![node.id],
+                    routes,
+                    message: format!(
+                        "{}: upstream next hop {} is one of its own addresses",
+                        node.name, parent_address
+                    ),
+                });
+            } else if !has_parent {
+                findings.push(Finding {
+                    kind: FindingKind::OrphanedRouter,
+                    severity: Severity::Warning,
+                    nodes: vec![node.id],
+                    routes,
+                    message: format!(
+                        "{}: upstream next hop {} does not belong to any known router",
+                        node.name, parent_address
+                    ),
+                });
+            }
+        }
+        findings
+    }
+
+    fn unlisted_device_findings(&self) -> Vec<Finding> {
+        self.unlisted_devices
+            .iter()
+            .map(|device| {
+                let name = if device.name.is_empty() {
+                    String::new()
+                } else {
+                    format!(" ({})", device.name)
+                };
+                Finding {
+                    kind: FindingKind::UnlistedDevice,
+                    severity: Severity::Info,
+                    nodes: vec![device.seen_by],
+                    routes: Vec::new(),
+                    message: format!(
+                        "{}{name} [{}] is behind {} ({:?}) but is not in the router list",
+                        device.address, device.mac, self.nodes[device.seen_by].name, device.reason
+                    ),
+                }
+            })
+            .collect()
+    }
+
+    fn find_duplicate_hostnames(&self) -> Vec<Finding> {
+        let mut by_name: BTreeMap<&str, Vec<NodeId>> = BTreeMap::new();
+        for node in self.nodes.iter() {
+            by_name.entry(node.name.as_str()).or_default().push(node.id);
+        }
+        by_name
+            .into_iter()
+            .filter(|(_, nodes)| nodes.len() > 1)
+            .map(|(name, nodes)| Finding {
+                kind: FindingKind::DuplicateHostname,
+                severity: Severity::Warning,
+                message: format!(
+                    "{} routers are called \"{name}\" ({})",
+                    nodes.len(),
+                    nodes
+                        .iter()
+                        .map(|n| self.nodes[*n].router.connection.snmp_address.as_str())
+                        .collect::<Vec<_>>()
+                        .join(", ")
+                ),
+                routes: Vec::new(),
+                nodes,
+            })
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::router::fixtures::{route, router};
+
+    fn kinds(topology: &Topology) -> Vec<FindingKind> {
+        topology.findings.iter().map(|f| f.kind).collect()
+    }
+
+    #[test]
+    fn test_routing_loop() {
+        let topology = Topology::build(vec![
+            router("a", &["10.0.0.1/30"], vec![route("0.0.0.0", 0, "10.0.0.2")]),
+            router("b", &["10.0.0.2/30"], vec![route("0.0.0.0", 0, "10.0.0.1")]),
+        ]);
+        assert_eq!(kinds(&topology), vec![FindingKind::RoutingLoop]);
+        assert_eq!(topology.findings[0].nodes.len(), 2);
+        assert_eq!(topology.findings[0].routes.len(), 2);
+    }
+
+    #[test]
+    fn test_self_parent_and_orphan() {
+        let topology = Topology::build(vec![
+            router("a", &["10.0.0.1/30"], vec![route("0.0.0.0", 0, "10.0.0.1")]),
+            router("b", &["10.0.1.1/30"], vec![route("0.0.0.0", 0, "10.0.1.2")]),
+        ]);
+        assert_eq!(
+            kinds(&topology),
+            vec![FindingKind::SelfParent, FindingKind::OrphanedRouter]
+        );
+        assert_eq!(topology.findings_for(1).count(), 1);
+    }
+
+    #[test]
+    fn test_duplicate_hostname() {
+        let topology = Topology::build(vec![
+            router("MikroTik", &["10.0.0.1/30"], vec![]),
+            router("MikroTik", &["10.0.0.2/30"], vec![]),
+        ]);
+        assert_eq!(kinds(&topology), vec![FindingKind::DuplicateHostname]);
+        assert_eq!(topology.findings[0].nodes, vec![0, 1]);
+    }
+
+    #[test]
+    fn test_empty_vrf() {
+        let mut pe = router("pe", &["10.0.0.1/30"], vec![]);
+        pe.connection.vrf = Some("mgmt".to_string());
+        let topology = Topology::build(vec![pe]);
+        assert_eq!(kinds(&topology), vec![FindingKind::EmptyVrf]);
+    }
+}
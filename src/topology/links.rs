@@ -0,0 +1,185 @@
+use super::{EdgeKind, Evidence, NodeId, Topology};
+use crate::config::CONFIG;
+use ipnetwork::IpNetwork;
+use serde::Serialize;
+use std::{collections::BTreeMap, net::IpAddr};
+
+// Routers sharing a subnet are very likely cabled together
+const SHARED_SUBNET_CONFIDENCE: f32 = 0.8;
+
+/// Whether a link joins exactly two routers or is a shared segment.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+pub enum LinkKind {
+    /// A /30, /31 (or IPv6 /126, /127) between two routers
+    PointToPoint,
+    /// A LAN segment that can hold many routers
+    MultiAccess,
+}
+
+/// One router's presence on a link.
+#[derive(Debug, Clone, Serialize)]
+pub struct LinkEndpoint {
+    pub node: NodeId,
+    pub address: IpAddr,
+    pub interface_index: i32,
+}
+
+/// A physical (or at least layer-2) link inferred from routers having
+/// addresses in the same connected subnet.
+#[derive(Debug, Clone, Serialize)]
+pub struct Link {
+    pub subnet: IpNetwork,
+    pub kind: LinkKind,
+    pub endpoints: Vec<LinkEndpoint>,
+    /// More routers are on this subnet than its kind allows for
+    pub overpopulated: bool,
+}
+
+impl Link {
+    /// The most routers we expect to find on this link.
+    pub fn expected_routers(&self) -> usize {
+        match self.kind {
+            LinkKind::PointToPoint => 2,
+            LinkKind::MultiAccess => CONFIG.max_routers_per_lan,
+        }
+    }
+
+    /// The distinct nodes on this link.
+    pub fn nodes(&self) -> Vec<NodeId> {
+        let mut nodes: Vec<NodeId> = self.endpoints.iter().map(|e| e.node).collect();
+        nodes.dedup();
+        nodes
+    }
+}
+
+fn link_kind(subnet: &IpNetwork) -> LinkKind {
+    let host_bits = if subnet.is_ipv4() { 32 } else { 128 } - subnet.prefix();
+    if host_bits <= 2 {
+        LinkKind::PointToPoint
+    } else {
+        LinkKind::MultiAccess
+    }
+}
+
+impl Topology {
+    /// Group every router's connected (non host-route) subnets, and record a
+    /// link wherever two or more routers share one.
+    pub(crate) fn infer_links(&mut self) {
+        let mut subnets: BTreeMap<IpNetwork, Vec<LinkEndpoint>> = BTreeMap::new();
+        for node in self.nodes.iter() {
+            for ip in node.router.ip_table.ips.iter() {
+                let Some(network) = ip.ip_network() else {
+                    continue;
+                };
+                if network.prefix() >= if network.is_ipv4() { 32 } else { 128 }
+                    || ip.address.is_loopback()
+                {
+                    continue;
+                }
+                let Ok(subnet) = IpNetwork::new(network.network(), network.prefix()) else {
+                    continue;
+                };
+                subnets.entry(subnet).or_default().push(LinkEndpoint {
+                    node: node.id,
+                    address: ip.address,
+                    interface_index: ip.interface_index,
+                });
+            }
+        }
+
+        for (subnet, endpoints) in subnets {
+            let mut link = Link {
+                subnet,
+                kind: link_kind(&subnet),
+                endpoints,
+                overpopulated: false,
+            };
+            let nodes = link.nodes();
+            if nodes.len() < 2 {
+                continue;
+            }
+            if nodes.len() > link.expected_routers() {
+                link.overpopulated = true;
+                tracing::warn!(
+                    "{subnet} is shared by {} routers, more than expected for a {:?} link",
+                    nodes.len(),
+                    link.kind
+                );
+            }
+            for (i, a) in nodes.iter().enumerate() {
+                for b in nodes.iter().skip(i + 1) {
+                    self.add_edge(
+                        *a,
+                        *b,
+                        EdgeKind::ConnectedSubnet,
+                        SHARED_SUBNET_CONFIDENCE,
+                        Evidence::SharedSubnet { subnet },
+                    );
+                }
+            }
+            self.links.push(link);
+        }
+    }
+
+    /// Links that `node` is part of.
+    pub fn links_of(&self, node: NodeId) -> impl Iterator<Item = &Link> {
+        self.links
+            .iter()
+            .filter(move |l| l.endpoints.iter().any(|e| e.node == node))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::router::fixtures::router;
+
+    #[test]
+    fn test_point_to_point_and_lan() {
+        let mut topology = Topology::from_routers(vec![
+            router(
+                "a",
+                &["10.0.0.1/30", "192.168.1.1/24", "10.255.0.1/32"],
+                vec![],
+            ),
+            router(
+                "b",
+                &["10.0.0.2/30", "192.168.1.2/24", "10.255.0.2/32"],
+                vec![],
+            ),
+            router("c", &["192.168.1.3/24"], vec![]),
+        ]);
+        topology.infer_links();
+        assert_eq!(topology.links.len(), 2);
+
+        let p2p = topology
+            .links
+            .iter()
+            .find(|l| l.kind == LinkKind::PointToPoint)
+            .unwrap();
+        assert_eq!(p2p.subnet, "10.0.0.0/30".parse::<IpNetwork>().unwrap());
+        assert_eq!(p2p.nodes(), vec![0, 1]);
+
+        let lan = topology
+            .links
+            .iter()
+            .find(|l| l.kind == LinkKind::MultiAccess)
+            .unwrap();
+        assert_eq!(lan.nodes(), vec![0, 1, 2]);
+        assert!(!lan.overpopulated);
+
+        // a-b share two subnets (one edge), plus a-c and b-c on the LAN
+        assert_eq!(topology.edges.len(), 3);
+    }
+
+    #[test]
+    fn test_overpopulated_point_to_point() {
+        let mut topology = Topology::from_routers(vec![
+            router("a", &["10.0.0.1/30"], vec![]),
+            router("b", &["10.0.0.2/30"], vec![]),
+            router("c", &["10.0.0.3/30"], vec![]),
+        ]);
+        topology.infer_links();
+        assert!(topology.links[0].overpopulated);
+    }
+}
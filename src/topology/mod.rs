@@ -0,0 +1,381 @@
+use crate::router::{Interface, NextHopResolution, Router, UpstreamRoute};
+use serde::Serialize;
+use std::{
+    collections::{HashSet, VecDeque},
+    net::IpAddr,
+    sync::OnceLock,
+};
+mod edge;
+pub use edge::{Edge, EdgeKind, Evidence, Utilisation};
+mod bgp;
+mod bridging;
+mod discovery;
+pub use discovery::{DiscoveryReason, UnlistedDevice};
+mod findings;
+pub use findings::{Finding, Severity};
+mod links;
+pub use links::Link;
+mod neighbors;
+mod ospf;
+mod parents;
+mod text;
+mod traffic;
+mod wireless;
+
+/// Index of a node within a `Topology`
+pub type NodeId = usize;
+
+/// A router and everything we know about it.
+#[derive(Debug, Serialize)]
+pub struct Node {
+    pub id: NodeId,
+    pub name: String,
+    pub router: Router,
+    /// The route used to look for this node's parent
+    pub upstream: Option<UpstreamRoute>,
+    /// How the upstream next hop was recursively resolved, if it was
+    pub resolution: Option<NextHopResolution>,
+}
+
+impl Node {
+    /// The address of the router we expect to be this node's parent: the
+    /// resolved next hop if resolution succeeded, otherwise the upstream
+    /// route's own next hop.
+    pub fn parent_address(&self) -> Option<IpAddr> {
+        let upstream = self.upstream.as_ref()?;
+        Some(
+            self.resolution
+                .as_ref()
+                .and_then(|r| r.connected_next_hop())
+                .unwrap_or(upstream.next_hop),
+        )
+    }
+}
+
+/// A node and the tree of nodes beneath it (following preferred parents).
+#[derive(Debug, Serialize)]
+pub struct Subtree {
+    pub node: NodeId,
+    pub children: Vec<Subtree>,
+}
+
+/// Preferred parents and the children they imply, worked out once from the
+/// parent edges rather than on every query.
+#[derive(Debug)]
+struct Hierarchy {
+    /// Indexed by node
+    preferred_parent: Vec<Option<NodeId>>,
+    /// Indexed by node, in node order
+    children: Vec<Vec<NodeId>>,
+}
+
+impl Hierarchy {
+    fn new(nodes: usize, edges: &[Edge]) -> Self {
+        let mut best: Vec<Option<&Edge>> = vec![None; nodes];
+        for edge in edges.iter().filter(|e| e.kind == EdgeKind::Parent) {
+            // The first of equally confident edges wins, as in `parent_edges`
+            match best[edge.from] {
+                Some(current) if current.confidence >= edge.confidence => {}
+                _ => best[edge.from] = Some(edge),
+            }
+        }
+        let preferred_parent: Vec<Option<NodeId>> =
+            best.into_iter().map(|e| e.map(|e| e.to)).collect();
+        let mut children = vec![Vec::new(); nodes];
+        for (node, parent) in preferred_parent.iter().enumerate() {
+            if let Some(parent) = parent {
+                children[*parent].push(node);
+            }
+        }
+        Self {
+            preferred_parent,
+            children,
+        }
+    }
+}
+
+/// The network graph: routers as nodes, joined by typed edges. Nodes may
+/// have several parents, and cycles are allowed; the query methods are all
+/// cycle-safe.
+#[derive(Debug, Default, Serialize)]
+pub struct Topology {
+    pub nodes: Vec<Node>,
+    pub edges: Vec<Edge>,
+    pub links: Vec<Link>,
+    pub findings: Vec<Finding>,
+    /// Managed devices seen in ARP caches that aren't in the router list
+    pub unlisted_devices: Vec<UnlistedDevice>,
+    /// Built on first use, and dropped whenever a parent edge is added
+    #[serde(skip)]
+    hierarchy: OnceLock<Hierarchy>,
+}
+
+impl Topology {
+    /// Build a topology from the polled routers, inferring every kind of
+    /// edge we know about.
+    pub fn build(routers: Vec<Router>) -> Self {
+        let mut topology = Self::from_routers(routers);
+        topology.infer_parents();
+        topology.infer_links();
+        topology.infer_neighbor_edges();
+        topology.infer_bgp_edges();
+        topology.infer_ospf_edges();
+        topology.infer_wireless_edges();
+        topology.attach_utilisation();
+        topology.discover_unlisted_devices();
+        topology.detect_findings();
+        topology
+    }
+
+    /// Create a topology with one node per router and no edges.
+    pub fn from_routers(routers: Vec<Router>) -> Self {
+        let nodes = routers
+            .into_iter()
+            .enumerate()
+            .map(|(id, router)| Node {
+                id,
+                name: router.system_info.hostname.clone(),
+                router,
+                upstream: None,
+                resolution: None,
+            })
+            .collect();
+        Self {
+            nodes,
+            edges: Vec::new(),
+            links: Vec::new(),
+            findings: Vec::new(),
+            unlisted_devices: Vec::new(),
+            hierarchy: OnceLock::new(),
+        }
+    }
+
+    /// Add an edge. If an equivalent edge already exists its evidence is
+    /// merged and the higher confidence is kept.
+    pub fn add_edge(
+        &mut self,
+        from: NodeId,
+        to: NodeId,
+        kind: EdgeKind,
+        confidence: f32,
+        evidence: Evidence,
+    ) {
+        if kind == EdgeKind::Parent {
+            self.hierarchy.take();
+        }
+        if let Some(edge) = self
+            .edges
+            .iter_mut()
+            .find(|e| e.kind == kind && e.connects(from, to))
+        {
+            edge.confidence = edge.confidence.max(confidence);
+            edge.evidence.push(evidence);
+        } else {
+            self.edges.push(Edge {
+                from,
+                to,
+                kind,
+                confidence,
+                evidence: vec![evidence],
+                utilisation: None,
+            });
+        }
+    }
+
+    /// The node owning `address`, ignoring `except` (usually the asking node).
+    pub fn find_owner(&self, address: IpAddr, except: Option<NodeId>) -> Option<NodeId> {
+        self.nodes
+            .iter()
+            .find(|n| Some(n.id) != except && n.router.owns_address(address))
+            .map(|n| n.id)
+    }
+
+    /// All edges touching `node`.
+    pub fn edges_of(&self, node: NodeId) -> impl Iterator<Item = &Edge> {
+        self.edges
+            .iter()
+            .filter(move |e| e.from == node || e.to == node)
+    }
+
+    /// Every parent edge of `node`, most confident first.
+    pub fn parent_edges(&self, node: NodeId) -> Vec<&Edge> {
+        let mut parents: Vec<&Edge> = self
+            .edges
+            .iter()
+            .filter(|e| e.kind == EdgeKind::Parent && e.from == node)
+            .collect();
+        parents.sort_by(|a, b| b.confidence.total_cmp(&a.confidence));
+        parents
+    }
+
+    /// Every parent of `node`, most confident first.
+    pub fn parents(&self, node: NodeId) -> Vec<NodeId> {
+        self.parent_edges(node).iter().map(|e| e.to).collect()
+    }
+
+    fn hierarchy(&self) -> &Hierarchy {
+        self.hierarchy
+            .get_or_init(|| Hierarchy::new(self.nodes.len(), &self.edges))
+    }
+
+    /// The most confident parent of `node`, used when laying out a tree.
+    pub fn preferred_parent(&self, node: NodeId) -> Option<NodeId> {
+        self.hierarchy().preferred_parent[node]
+    }
+
+    /// The interface on `node` that faces its parent, if known.
+    pub fn upstream_interface(&self, node: NodeId) -> Option<&Interface> {
+        let node = &self.nodes[node];
+        node.router.interface_towards(node.parent_address()?)
+    }
+
+    /// Nodes whose preferred parent is `node`.
+    pub fn children(&self, node: NodeId) -> Vec<NodeId> {
+        self.hierarchy().children[node].clone()
+    }
+
+    /// Nodes with no parent at all.
+    pub fn roots(&self) -> Vec<NodeId> {
+        let hierarchy = self.hierarchy();
+        self.nodes
+            .iter()
+            .filter(|n| hierarchy.preferred_parent[n.id].is_none())
+            .map(|n| n.id)
+            .collect()
+    }
+
+    /// Follow preferred parents from `node` upwards. The path starts with
+    /// `node` itself and stops at a root, or just before a node repeats.
+    pub fn path_to_root(&self, node: NodeId) -> Vec<NodeId> {
+        let hierarchy = self.hierarchy();
+        let mut path = vec![node];
+        let mut visited = HashSet::from([node]);
+        let mut current = node;
+        while let Some(parent) = hierarchy.preferred_parent[current] {
+            if !visited.insert(parent) {
+                break;
+            }
+            path.push(parent);
+            current = parent;
+        }
+        path
+    }
+
+    /// Every node beneath `node`, breadth first.
+    pub fn descendants(&self, node: NodeId) -> Vec<NodeId> {
+        let mut result = Vec::new();
+        let mut visited = HashSet::from([node]);
+        let mut queue = VecDeque::from([node]);
+        while let Some(current) = queue.pop_front() {
+            for &child in self.hierarchy().children[current].iter() {
+                if visited.insert(child) {
+                    result.push(child);
+                    queue.push_back(child);
+                }
+            }
+        }
+        result
+    }
+
+    /// The tree beneath `node`. Nodes are only visited once, so a cycle is
+    /// cut at the point it would repeat.
+    pub fn subtree(&self, node: NodeId) -> Subtree {
+        let mut visited = HashSet::new();
+        self.subtree_inner(node, &mut visited)
+    }
+
+    fn subtree_inner(&self, node: NodeId, visited: &mut HashSet<NodeId>) -> Subtree {
+        visited.insert(node);
+        let mut children = Vec::new();
+        for &child in self.hierarchy().children[node].iter() {
+            if !visited.contains(&child) {
+                children.push(self.subtree_inner(child, visited));
+            }
+        }
+        Subtree { node, children }
+    }
+
+    /// Trees covering every node: one per root, then one for each group of
+    /// nodes that is only reachable through a cycle.
+    pub fn forest(&self) -> Vec<Subtree> {
+        let mut visited = HashSet::new();
+        let mut forest = Vec::new();
+        let starts = self
+            .roots()
+            .into_iter()
+            .chain(self.nodes.iter().map(|n| n.id));
+        for start in starts {
+            if !visited.contains(&start) {
+                forest.push(self.subtree_inner(start, &mut visited));
+            }
+        }
+        forest
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::router::fixtures::{route, router};
+
+    // core <- agg <- edge1, edge2
+    fn test_topology() -> Topology {
+        Topology::build(vec![
+            router("core", &["10.0.0.1/30"], vec![]),
+            router(
+                "agg",
+                &["10.0.0.2/30", "10.0.1.1/29"],
+                vec![route("0.0.0.0", 0, "10.0.0.1")],
+            ),
+            router(
+                "edge1",
+                &["10.0.1.2/29"],
+                vec![route("0.0.0.0", 0, "10.0.1.1")],
+            ),
+            router(
+                "edge2",
+                &["10.0.1.3/29"],
+                vec![route("0.0.0.0", 0, "10.0.1.1")],
+            ),
+        ])
+    }
+
+    #[test]
+    fn test_parent_inference() {
+        let topology = test_topology();
+        assert_eq!(topology.roots(), vec![0]);
+        assert_eq!(topology.preferred_parent(1), Some(0));
+        assert_eq!(topology.children(1), vec![2, 3]);
+    }
+
+    #[test]
+    fn test_path_and_descendants() {
+        let topology = test_topology();
+        assert_eq!(topology.path_to_root(3), vec![3, 1, 0]);
+        assert_eq!(topology.descendants(0), vec![1, 2, 3]);
+        assert_eq!(topology.subtree(1).children.len(), 2);
+    }
+
+    #[test]
+    fn test_cycle_is_safe() {
+        let mut topology = test_topology();
+        assert_eq!(topology.roots(), vec![0]);
+        // Make core point back at agg
+        let evidence = topology.edges[0].evidence[0].clone();
+        topology.add_edge(0, 1, EdgeKind::Parent, 0.9, evidence);
+        assert!(topology.roots().is_empty());
+        assert_eq!(topology.path_to_root(2), vec![2, 1, 0]);
+        let forest = topology.forest();
+        assert_eq!(forest.len(), 1);
+        assert_eq!(topology.descendants(0).len(), 3);
+    }
+
+    #[test]
+    fn test_multiple_parents() {
+        let mut topology = test_topology();
+        let evidence = topology.edges[0].evidence[0].clone();
+        topology.add_edge(2, 3, EdgeKind::Parent, 0.5, evidence);
+        assert_eq!(topology.parents(2), vec![1, 3]);
+        assert_eq!(topology.preferred_parent(2), Some(1));
+    }
+}
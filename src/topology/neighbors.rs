@@ -0,0 +1,130 @@
+use super::{EdgeKind, Evidence, NodeId, Topology};
+use crate::router::Neighbor;
+use std::net::IpAddr;
+
+// A neighbor discovery protocol saw the two devices on the same wire
+const NEIGHBOR_CONFIDENCE: f32 = 1.0;
+
+/// CDP device IDs are often fully qualified, or carry a serial number in
+/// brackets, so compare only the bare host part, ignoring case. When both
+/// names are fully qualified the whole name has to match, and an address
+/// used as a name is never cut at its first dot.
+fn same_device_name(hostname: &str, reported: &str) -> bool {
+    fn split(name: &str) -> (&str, Option<&str>) {
+        let name = name.split('(').next().unwrap_or(name).trim();
+        if name.parse::<IpAddr>().is_ok() {
+            return (name, None);
+        }
+        match name.split_once('.') {
+            Some((host, domain)) => (host, Some(domain)),
+            None => (name, None),
+        }
+    }
+    let (host, domain) = split(hostname);
+    let (reported_host, reported_domain) = split(reported);
+    let domains_match = match (domain, reported_domain) {
+        (Some(a), Some(b)) => a.eq_ignore_ascii_case(b),
+        _ => true,
+    };
+    !host.is_empty() && host.eq_ignore_ascii_case(reported_host) && domains_match
+}
+
+impl Topology {
+    /// Turn every discovery-protocol neighbor that we can match to a known
+    /// router into a confirmed adjacency edge.
+    pub(crate) fn infer_neighbor_edges(&mut self) {
+        let mut found = Vec::new();
+        for node in self.nodes.iter() {
+            for neighbor in node.router.neighbors.neighbors.iter() {
+                let Some(other) = self.find_neighbor(neighbor, node.id) else {
+                    continue;
+                };
+                let local_port = node
+                    .router
+                    .neighbor_interface(neighbor)
+                    .map(|i| i.short_name().to_string())
+                    .unwrap_or_else(|| neighbor.local_port_name.clone());
+                found.push((
+                    node.id,
+                    other,
+                    Evidence::Neighbor {
+                        protocol: neighbor.protocol,
+                        local_port,
+                        remote_port: neighbor.port_id.clone(),
+                    },
+                ));
+            }
+        }
+        for (from, to, evidence) in found {
+            self.add_edge(from, to, EdgeKind::Neighbor, NEIGHBOR_CONFIDENCE, evidence);
+        }
+    }
+
+    /// Match a neighbor entry to a known router: by management address,
+    /// then chassis MAC, then (unique) system name.
+    pub fn find_neighbor(&self, neighbor: &Neighbor, seen_by: NodeId) -> Option<NodeId> {
+        if let Some(node) = neighbor
+            .management_addresses
+            .iter()
+            .find_map(|address| self.find_owner(*address, Some(seen_by)))
+        {
+            return Some(node);
+        }
+        if let Some(node) = self
+            .nodes
+            .iter()
+            .find(|n| n.id != seen_by && n.router.has_mac(&neighbor.chassis_id))
+        {
+            return Some(node.id);
+        }
+        let mut by_name = self
+            .nodes
+            .iter()
+            .filter(|n| n.id != seen_by && same_device_name(&n.name, &neighbor.system_name));
+        match (by_name.next(), by_name.next()) {
+            (Some(node), None) => Some(node.id),
+            _ => None,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::router::{fixtures::router, NeighborProtocol};
+
+    #[test]
+    fn test_neighbor_edge() {
+        let mut a = router("a", &["10.0.0.1/30"], vec![]);
+        a.neighbors.neighbors.push(Neighbor {
+            protocol: NeighborProtocol::Lldp,
+            local_port: 1,
+            local_port_name: "ether1".to_string(),
+            chassis_id: "4C:5E:0C:12:34:56".to_string(),
+            port_id: "ether2".to_string(),
+            port_description: String::new(),
+            system_name: "b".to_string(),
+            system_description: String::new(),
+            platform: String::new(),
+            management_addresses: vec!["192.168.88.1".parse().unwrap()],
+        });
+        let b = router("b", &["10.0.0.2/30", "192.168.88.1/24"], vec![]);
+        let mut topology = Topology::from_routers(vec![a, b]);
+        topology.infer_neighbor_edges();
+        assert_eq!(topology.edges.len(), 1);
+        assert_eq!(topology.edges[0].kind, EdgeKind::Neighbor);
+        assert!(topology.edges[0].connects(1, 0));
+    }
+
+    #[test]
+    fn test_same_device_name() {
+        assert!(same_device_name("agg-1", "AGG-1.example.net"));
+        assert!(same_device_name("agg-1", "agg-1(FOC1234X0YZ)"));
+        assert!(!same_device_name("agg-1", "agg-2"));
+        assert!(!same_device_name("", ""));
+        assert!(same_device_name("agg-1.example.net", "agg-1.EXAMPLE.net"));
+        assert!(!same_device_name("agg-1.example.net", "agg-1.example.org"));
+        assert!(!same_device_name("10.0.0.1", "10.0.0.2"));
+        assert!(same_device_name("10.0.0.1", "10.0.0.1"));
+    }
+}
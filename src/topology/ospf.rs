@@ -0,0 +1,126 @@
+use super::{EdgeKind, Evidence, NodeId, Topology};
+use crate::router::{OspfNeighbor, OspfNeighborState};
+use std::{collections::BTreeMap, net::Ipv4Addr};
+
+// A full adjacency means the link is up and carrying routing; anything
+// less means it's configured but not (yet) working
+const FULL_CONFIDENCE: f32 = 1.0;
+const PARTIAL_CONFIDENCE: f32 = 0.6;
+
+impl Topology {
+    /// Add an edge for every OSPF adjacency between two known routers.
+    pub(crate) fn infer_ospf_edges(&mut self) {
+        let mut found = Vec::new();
+        for node in self.nodes.iter() {
+            let ospf = &node.router.ospf;
+            for neighbor in ospf.neighbors.iter() {
+                let Some(other) = self.find_ospf_neighbor(neighbor, node.id) else {
+                    continue;
+                };
+                let confidence = if neighbor.state == OspfNeighborState::Full {
+                    FULL_CONFIDENCE
+                } else {
+                    PARTIAL_CONFIDENCE
+                };
+                found.push((
+                    node.id,
+                    other,
+                    confidence,
+                    Evidence::OspfAdjacency {
+                        area: ospf.area_of(neighbor.address, &node.router.ip_table),
+                        state: neighbor.state,
+                    },
+                ));
+            }
+        }
+        for (from, to, confidence, evidence) in found {
+            self.add_edge(from, to, EdgeKind::OspfAdjacency, confidence, evidence);
+        }
+    }
+
+    /// Match an OSPF neighbor to a known router, by router-id or address.
+    /// A neighbor whose router-id isn't known yet (0.0.0.0) is only matched
+    /// by address.
+    pub fn find_ospf_neighbor(&self, neighbor: &OspfNeighbor, seen_by: NodeId) -> Option<NodeId> {
+        Some(neighbor.router_id)
+            .filter(|id| !id.is_unspecified())
+            .and_then(|id| {
+                self.nodes
+                    .iter()
+                    .find(|n| n.id != seen_by && n.router.ospf.router_id == Some(id))
+            })
+            .map(|n| n.id)
+            .or_else(|| self.find_owner(neighbor.address, Some(seen_by)))
+    }
+
+    /// Routers grouped by the OSPF areas they have interfaces in. Area border
+    /// routers appear in every area they touch.
+    pub fn ospf_areas(&self) -> BTreeMap<Ipv4Addr, Vec<NodeId>> {
+        let mut areas: BTreeMap<Ipv4Addr, Vec<NodeId>> = BTreeMap::new();
+        for node in self.nodes.iter() {
+            let mut node_areas: Vec<Ipv4Addr> = node
+                .router
+                .ospf
+                .interfaces
+                .iter()
+                .map(|iface| iface.area_id)
+                .chain(node.router.ospf.areas.iter().map(|area| area.area_id))
+                .collect();
+            node_areas.sort();
+            node_areas.dedup();
+            for area in node_areas {
+                areas.entry(area).or_default().push(node.id);
+            }
+        }
+        areas
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::router::fixtures::router;
+
+    #[test]
+    fn test_ospf_adjacency() {
+        let mut a = router("a", &["10.0.0.1/30", "10.255.0.1/32"], vec![]);
+        a.ospf.router_id = Some("10.255.0.1".parse().unwrap());
+        a.ospf.neighbors = vec![OspfNeighbor {
+            address: "10.0.0.2".parse().unwrap(),
+            router_id: "10.255.0.2".parse().unwrap(),
+            state: OspfNeighborState::Full,
+        }];
+        let mut b = router("b", &["10.0.0.2/30", "10.255.0.2/32"], vec![]);
+        b.ospf.router_id = Some("10.255.0.2".parse().unwrap());
+        b.ospf.neighbors = vec![OspfNeighbor {
+            address: "10.0.0.1".parse().unwrap(),
+            router_id: "10.255.0.1".parse().unwrap(),
+            state: OspfNeighborState::ExchangeStart,
+        }];
+        let mut topology = Topology::from_routers(vec![a, b]);
+        topology.infer_ospf_edges();
+        assert_eq!(topology.edges.len(), 1);
+        assert_eq!(topology.edges[0].kind, EdgeKind::OspfAdjacency);
+        assert_eq!(topology.edges[0].confidence, FULL_CONFIDENCE);
+        assert_eq!(topology.edges[0].evidence.len(), 2);
+    }
+
+    #[test]
+    fn test_unspecified_router_id() {
+        let mut a = router("a", &["10.0.0.1/30"], vec![]);
+        a.ospf.neighbors = vec![OspfNeighbor {
+            address: "10.0.0.2".parse().unwrap(),
+            router_id: "0.0.0.0".parse().unwrap(),
+            state: OspfNeighborState::Init,
+        }];
+        let mut b = router("b", &["10.0.1.1/30"], vec![]);
+        b.ospf.router_id = Some("0.0.0.0".parse().unwrap());
+        let c = router("c", &["10.0.0.2/30"], vec![]);
+        let topology = Topology::from_routers(vec![a, b, c]);
+        // Matched by address, not by b's equally unset router-id
+        assert_eq!(
+            topology.find_ospf_neighbor(&topology.nodes[0].router.ospf.neighbors[0], 0),
+            Some(2)
+        );
+    }
+}
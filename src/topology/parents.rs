@@ -0,0 +1,78 @@
+use super::{EdgeKind, Evidence, Topology};
+use crate::{config::CONFIG, router::UpstreamSource};
+
+// How much we trust each way of finding a parent
+const DIRECT_CONFIDENCE: f32 = 0.9;
+const RESOLVED_CONFIDENCE: f32 = 0.7;
+const FALLBACK_CONFIDENCE: f32 = 0.5;
+
+impl Topology {
+    /// Find each router's parent by following its upstream (usually default)
+    /// route to the router that owns the next hop.
+    pub(crate) fn infer_parents(&mut self) {
+        for idx in 0..self.nodes.len() {
+            let me = &self.nodes[idx].router;
+            let Some(upstream) =
+                me.upstream_route(&CONFIG.upstream_prefixes, CONFIG.management_seed)
+            else {
+                tracing::warn!(
+                    "{} has no default or upstream route; treating it as a root",
+                    self.nodes[idx].name
+                );
+                continue;
+            };
+            let mut resolution = None;
+
+            if !me.is_connected(upstream.next_hop) && CONFIG.enable_next_hop_lookup {
+                tracing::info!(
+                    "{} has an off-net upstream next hop of {}, resolving it",
+                    me.system_info.hostname,
+                    upstream.next_hop
+                );
+                let resolved = me.resolve_next_hop(upstream.next_hop, CONFIG.max_next_hop_depth);
+                resolved.log_trace(&me.system_info.hostname);
+                if resolved.connected_next_hop().is_none() {
+                    tracing::warn!(
+                        "{}: unable to resolve next hop {}: {:?}",
+                        me.system_info.hostname,
+                        upstream.next_hop,
+                        resolved.outcome
+                    );
+                }
+                resolution = Some(resolved);
+            }
+            // As Node::parent_address: the resolved next hop, if any
+            let parent_address = resolution
+                .as_ref()
+                .and_then(|r| r.connected_next_hop())
+                .unwrap_or(upstream.next_hop);
+            self.nodes[idx].upstream = Some(upstream.clone());
+            self.nodes[idx].resolution = resolution.clone();
+
+            let Some(parent) = self.find_owner(parent_address, Some(idx)) else {
+                continue;
+            };
+            tracing::info!(
+                "Found a likely parent for {} via {:?}: {}",
+                self.nodes[idx].name,
+                upstream.source,
+                self.nodes[parent].name
+            );
+            let confidence = match (&upstream.source, &resolution) {
+                (UpstreamSource::DefaultRoute, None) => DIRECT_CONFIDENCE,
+                (UpstreamSource::DefaultRoute, Some(_)) => RESOLVED_CONFIDENCE,
+                _ => FALLBACK_CONFIDENCE,
+            };
+            self.add_edge(
+                idx,
+                parent,
+                EdgeKind::Parent,
+                confidence,
+                Evidence::UpstreamRoute {
+                    route: upstream,
+                    resolution,
+                },
+            );
+        }
+    }
+}
@@ -0,0 +1,104 @@
+use super::{Subtree, Topology};
+
+impl Topology {
+    /// Print the hierarchy as an indented tree on stdout.
+    pub fn print_tree(&self) {
+        for tree in self.forest() {
+            println!("{}", self.nodes[tree.node].name);
+            self.print_children(&tree, 3);
+        }
+    }
+
+    fn print_children(&self, tree: &Subtree, indent: usize) {
+        for child in tree.children.iter() {
+            let node = &self.nodes[child.node];
+            let load = self
+                .parent_edges(child.node)
+                .first()
+                .and_then(|e| e.utilisation.as_ref())
+                .and_then(|u| u.fraction)
+                .map(|f| format!(" [{:.0}% used]", f * 100.0))
+                .unwrap_or_default();
+            match self.upstream_interface(child.node) {
+                Some(interface) => println!(
+                    "{}-> {} via {}{load}",
+                    "-".repeat(indent),
+                    node.name,
+                    interface.label()
+                ),
+                None => println!("{}-> {}{load}", "-".repeat(indent), node.name),
+            }
+            self.print_children(child, indent + 3);
+        }
+    }
+
+    /// Print the bridge port each router appears to be plugged into.
+    pub fn print_bridge_ports(&self) {
+        for node in self.nodes.iter() {
+            let Some(location) = self.attachment_port(node.id) else {
+                continue;
+            };
+            let bridge = &self.nodes[location.node];
+            let port = location
+                .if_index
+                .and_then(|i| bridge.router.interfaces.get(i))
+                .map(|i| i.short_name().to_string())
+                .unwrap_or_else(|| format!("port {}", location.port));
+            match location.vlan {
+                Some(vlan) => println!("{} is on {} {port} (VLAN {vlan})", node.name, bridge.name),
+                None => println!("{} is on {} {port}", node.name, bridge.name),
+            }
+        }
+    }
+
+    /// Print every radio link between known devices with its signal and
+    /// capacity.
+    pub fn print_wireless_links(&self) {
+        let mbps = |v: Option<f64>| v.map_or("?".to_string(), |v| format!("{v:.0}"));
+        for edge in self.edges.iter() {
+            let Some(link) = edge.wireless() else {
+                continue;
+            };
+            let (tx, rx) = link.capacity_mbps();
+            let mut details = Vec::new();
+            if let Some(signal) = link.signal_dbm {
+                details.push(format!("signal {signal:.0} dBm"));
+            }
+            if let Some(snr) = link.snr() {
+                details.push(format!("SNR {snr:.0} dB"));
+            }
+            details.push(format!("{}/{} Mbps", mbps(tx), mbps(rx)));
+            println!(
+                "{} <-> {}: {}",
+                self.nodes[edge.from].name,
+                self.nodes[edge.to].name,
+                details.join(", ")
+            );
+        }
+    }
+
+    /// Print each apparent route reflector with the routers peering with it.
+    pub fn print_route_reflectors(&self) {
+        for (reflector, clients) in self.route_reflectors() {
+            let bgp = &self.nodes[reflector].router.bgp;
+            println!(
+                "{} (AS {}) reflects for:",
+                self.nodes[reflector].name,
+                bgp.local_as.unwrap_or_default()
+            );
+            for client in clients {
+                println!("---> {}", self.nodes[client].name);
+            }
+        }
+    }
+
+    /// Print the routers in each OSPF area.
+    pub fn print_ospf_areas(&self) {
+        for (area, members) in self.ospf_areas() {
+            println!("OSPF area {area}:");
+            for member in members {
+                println!("---> {}", self.nodes[member].name);
+            }
+        }
+    }
+}
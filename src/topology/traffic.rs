@@ -0,0 +1,100 @@
+use super::{Edge, Evidence, NodeId, Topology, Utilisation};
+use crate::router::{Interface, Router};
+
+/// The interface on `router` named `name` (ifName or ifDescr).
+fn interface_named<'a>(router: &'a Router, name: &str) -> Option<&'a Interface> {
+    router
+        .interfaces
+        .interfaces
+        .iter()
+        .find(|i| i.short_name() == name || i.description == name)
+}
+
+impl Topology {
+    /// The interface on `node` (one end of `edge`) that carries it, worked
+    /// out from the edge's evidence. Logical edges (BGP, OSPF) have none.
+    pub fn edge_interface(&self, edge: &Edge, node: NodeId) -> Option<&Interface> {
+        let router = &self.nodes[node].router;
+        let is_from = node == edge.from;
+        edge.evidence.iter().find_map(|evidence| match evidence {
+            Evidence::UpstreamRoute { route, .. } if is_from => {
+                router.interface_towards(route.next_hop)
+            }
+            Evidence::SharedSubnet { subnet } => router.interface_towards(subnet.ip()),
+            Evidence::Neighbor { local_port, .. } if is_from => interface_named(router, local_port),
+            Evidence::Neighbor { remote_port, .. } => interface_named(router, remote_port),
+            Evidence::Wireless { link } if is_from => router.interfaces.get(link.local_interface?),
+            _ => None,
+        })
+    }
+
+    /// Work out the load on every edge whose interface has measured rates,
+    /// preferring the `from` end's counters.
+    pub(crate) fn attach_utilisation(&mut self) {
+        let mut found = Vec::new();
+        for (i, edge) in self.edges.iter().enumerate() {
+            for (node, reversed) in [(edge.from, false), (edge.to, true)] {
+                let Some(interface) = self.edge_interface(edge, node) else {
+                    continue;
+                };
+                let Some(rates) = self.nodes[node].router.traffic.get(interface.index) else {
+                    continue;
+                };
+                let rates = if reversed { rates.reversed() } else { *rates };
+                found.push((
+                    i,
+                    Utilisation {
+                        interface: interface.short_name().to_string(),
+                        capacity_mbps: interface.speed_mbps,
+                        rates,
+                        fraction: rates.utilisation(interface.speed_mbps),
+                    },
+                ));
+                break;
+            }
+        }
+        for (i, utilisation) in found {
+            self.edges[i].utilisation = Some(utilisation);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::router::{fixtures::router, InterfaceRates};
+    use crate::topology::EdgeKind;
+
+    #[test]
+    fn test_utilisation_from_far_end() {
+        let a = router("a", &["10.0.0.1/30"], vec![]);
+        let mut b = router("b", &["10.0.0.2/30"], vec![]);
+        b.ip_table.ips[0].interface_index = 4;
+        b.interfaces.interfaces.push(Interface {
+            index: 4,
+            name: "ether4".to_string(),
+            speed_mbps: 100,
+            ..Default::default()
+        });
+        b.traffic.rates.insert(
+            4,
+            InterfaceRates {
+                in_bps: 20_000_000.0,
+                out_bps: 50_000_000.0,
+                ..Default::default()
+            },
+        );
+        let mut topology = Topology::from_routers(vec![a, b]);
+        topology.infer_links();
+        topology.attach_utilisation();
+        let edge = &topology.edges[0];
+        assert_eq!(edge.kind, EdgeKind::ConnectedSubnet);
+        let utilisation = edge.utilisation.as_ref().unwrap();
+        assert_eq!(utilisation.interface, "ether4");
+        assert_eq!(utilisation.fraction, Some(0.5));
+        // Measured on b, but reported as seen from a (the `from` end)
+        assert_eq!(edge.from, 0);
+        assert_eq!(utilisation.rates.in_bps, 50_000_000.0);
+        assert_eq!(utilisation.rates.out_bps, 20_000_000.0);
+    }
+}
@@ -0,0 +1,96 @@
+use super::{EdgeKind, Evidence, NodeId, Topology};
+use crate::router::WirelessLink;
+
+// A radio registration is as good as a neighbor table entry
+const WIRELESS_CONFIDENCE: f32 = 0.9;
+
+impl Topology {
+    /// Add an edge for every radio link whose far end is a known device.
+    pub(crate) fn infer_wireless_edges(&mut self) {
+        let mut found = Vec::new();
+        for node in self.nodes.iter() {
+            for link in node.router.wireless.links.iter() {
+                if let Some(other) = self.find_wireless_peer(link, node.id) {
+                    found.push((node.id, other, link.clone()));
+                }
+            }
+        }
+        for (from, to, link) in found {
+            // Keep the statistics oriented to the edge's `from` end
+            let link = match self
+                .edges
+                .iter()
+                .find(|e| e.kind == EdgeKind::Wireless && e.connects(from, to))
+            {
+                Some(edge) if edge.from != from => link.reversed(),
+                _ => link,
+            };
+            self.add_edge(
+                from,
+                to,
+                EdgeKind::Wireless,
+                WIRELESS_CONFIDENCE,
+                Evidence::Wireless { link },
+            );
+        }
+    }
+
+    /// Match the far end of a radio link to a known device, by its address
+    /// or by the MAC the radio registered.
+    pub fn find_wireless_peer(&self, link: &WirelessLink, seen_by: NodeId) -> Option<NodeId> {
+        link.remote_address
+            .and_then(|address| self.find_owner(address, Some(seen_by)))
+            .or_else(|| {
+                self.nodes
+                    .iter()
+                    .find(|n| n.id != seen_by && n.router.has_mac(&link.remote_mac))
+                    .map(|n| n.id)
+            })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::router::{fixtures::router, Interface};
+
+    #[test]
+    fn test_wireless_edge_from_both_ends() {
+        let mut ap = router("ap", &["10.0.0.1/24"], vec![]);
+        ap.interfaces.interfaces.push(Interface {
+            index: 1,
+            phys_address: "00:00:00:00:00:01".to_string(),
+            ..Default::default()
+        });
+        ap.wireless.links.push(WirelessLink {
+            remote_mac: "00:00:00:00:00:02".to_string(),
+            tx_rate_mbps: Some(300.0),
+            rx_rate_mbps: Some(200.0),
+            ..Default::default()
+        });
+        let mut station = router("station", &["10.0.0.2/24"], vec![]);
+        station.wireless.links.push(WirelessLink {
+            remote_address: Some("10.0.0.1".parse().unwrap()),
+            tx_rate_mbps: Some(200.0),
+            rx_rate_mbps: Some(300.0),
+            ..Default::default()
+        });
+        station.interfaces.interfaces.push(Interface {
+            index: 1,
+            phys_address: "00:00:00:00:00:02".to_string(),
+            ..Default::default()
+        });
+        let mut topology = Topology::from_routers(vec![ap, station]);
+        topology.infer_wireless_edges();
+        assert_eq!(topology.edges.len(), 1);
+        let edge = &topology.edges[0];
+        assert_eq!(edge.evidence.len(), 2);
+        // Both readings agree once oriented from the edge's `from` end
+        for evidence in edge.evidence.iter() {
+            let Evidence::Wireless { link } = evidence else {
+                panic!("unexpected evidence");
+            };
+            assert_eq!(link.capacity_mbps(), (Some(300.0), Some(200.0)));
+        }
+    }
+}